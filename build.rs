@@ -0,0 +1,38 @@
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+// Records the revision of the sources being compiled, so benchmark results name what they measured.
+// Only a checkout of this crate has one: when the crate is vendored or pulled from a registry there
+// is no `.git` next to the manifest, git is not run, and the revision is `unknown` rather than that
+// of whatever repository encloses it.
+fn main() {
+    let root = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+
+    let revision = root
+        .join(".git")
+        .exists()
+        .then(|| {
+            Command::new("git")
+                .args(["describe", "--always", "--dirty"])
+                .current_dir(&root)
+                .output()
+                .ok()
+        })
+        .flatten()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|revision| revision.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=GIT_REVISION={revision}");
+    for path in ["src", "Cargo.toml", "build.rs"] {
+        println!("cargo:rerun-if-changed={path}");
+    }
+    // A missing path would make cargo rerun the script on every build.
+    for path in [".git/HEAD", ".git/index"] {
+        if root.join(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::iter;
+use std::ops::{Index, IndexMut};
+
+#[derive(Debug)]
+pub(crate) struct Arena<T> {
+    slots: Vec<Option<T>>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+impl<T> Arena<T> {
+    pub(crate) fn new() -> Arena<T> {
+        Arena {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Number of slots, free or not. Appending this arena to another one costs this much.
+    pub(crate) fn slots(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub(crate) fn insert(&mut self, value: T) -> usize {
+        self.len += 1;
+
+        if let Some(index) = self.free.pop() {
+            self.slots[index] = Some(value);
+            index
+        } else {
+            self.slots.push(Some(value));
+            self.slots.len() - 1
+        }
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) -> T {
+        let value = self.slots[index].take().unwrap();
+        self.free.push(index);
+        self.len -= 1;
+
+        value
+    }
+
+    #[cfg(feature = "binomial")]
+    pub(crate) fn pair_mut(&mut self, a: usize, b: usize) -> (&mut T, &mut T) {
+        assert_ne!(a, b);
+        let (a, b) = if a < b {
+            let (low, high) = self.slots.split_at_mut(b);
+            (&mut low[a], &mut high[0])
+        } else {
+            let (low, high) = self.slots.split_at_mut(a);
+            (&mut high[0], &mut low[b])
+        };
+
+        (a.as_mut().unwrap(), b.as_mut().unwrap())
+    }
+
+    pub(crate) fn append(&mut self, other: Arena<T>) -> Vec<Option<usize>> {
+        other
+            .slots
+            .into_iter()
+            .map(|slot| slot.map(|value| self.insert(value)))
+            .collect()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.slots.clear();
+        self.free.clear();
+        self.len = 0;
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().flatten()
+    }
+}
+
+impl<T> Index<usize> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.slots[index].as_ref().unwrap()
+    }
+}
+
+impl<T> IndexMut<usize> for Arena<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.slots[index].as_mut().unwrap()
+    }
+}
+
+impl<T> IntoIterator for Arena<T> {
+    type Item = T;
+    type IntoIter = iter::Flatten<std::vec::IntoIter<Option<T>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.slots.into_iter().flatten()
+    }
+}
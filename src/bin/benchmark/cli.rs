@@ -0,0 +1,508 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub const USAGE: &str = "\
+Usage: benchmark [OPTIONS]
+       benchmark compare [COMPARE OPTIONS] <BASELINE> <CANDIDATE>
+
+Options:
+  -n, --size <N>           Keys inserted and then deleted in every run [default: 1000000]
+  -r, --runs <RUNS>        Number of runs, each seeded with the previous seed plus one [default: 15]
+  -s, --seed <SEED>        Seed of the first run [default: 131254153214]
+      --heaps <LIST>       Comma-separated heaps to run [default: all]
+                           binary, binomial, randomized, pairing, fibonacci, 4-ary, 8-ary, leftist, skew
+      --operations <LIST>  Comma-separated operations to report: insert, delete [default: all]
+      --mode <MODE>        per-op times and prints every operation; batched times batches of
+                           operations and prints statistics per bucket [default: per-op]
+      --batch-size <OPS>   Operations timed together in batched mode [default: 100]
+      --bucket-size <OPS>  Operations grouped under each value of n [default: 10000]
+  -o, --output <PATH>      Write the results to PATH instead of stdout
+      --format <FORMAT>    Output format [default: csv]
+                           csv: one row per operation, or per bucket in batched mode
+                           stats-csv, json: run header followed by statistics per bucket
+  -h, --help               Print this help
+
+Compare options, for two files written with --format stats-csv or json:
+  -t, --threshold <PCT>    Slowdown, in percent, that counts as a regression [default: 5]
+      --confidence <LEVEL> Confidence level of the speedup intervals [default: 0.95]
+      --resamples <N>      Bootstrap resamples per heap and operation [default: 2000]
+  -s, --seed <SEED>        Seed of the bootstrap [default: 131254153214]
+
+compare exits with status 1 when the whole interval of some speedup lies below the threshold.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapKind {
+    Binary,
+    Binomial,
+    Randomized,
+    Pairing,
+    Fibonacci,
+    Quaternary,
+    Octonary,
+    Leftist,
+    Skew,
+}
+
+impl HeapKind {
+    pub const ALL: [HeapKind; 9] = [
+        HeapKind::Binary,
+        HeapKind::Binomial,
+        HeapKind::Randomized,
+        HeapKind::Pairing,
+        HeapKind::Fibonacci,
+        HeapKind::Quaternary,
+        HeapKind::Octonary,
+        HeapKind::Leftist,
+        HeapKind::Skew,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HeapKind::Binary => "binary",
+            HeapKind::Binomial => "binomial",
+            HeapKind::Randomized => "randomized",
+            HeapKind::Pairing => "pairing",
+            HeapKind::Fibonacci => "fibonacci",
+            HeapKind::Quaternary => "4-ary",
+            HeapKind::Octonary => "8-ary",
+            HeapKind::Leftist => "leftist",
+            HeapKind::Skew => "skew",
+        }
+    }
+
+    pub fn arity(self) -> Option<usize> {
+        match self {
+            HeapKind::Binary => Some(2),
+            HeapKind::Quaternary => Some(4),
+            HeapKind::Octonary => Some(8),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Insert,
+    Delete,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 2] = [Operation::Insert, Operation::Delete];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Operation::Insert => "insert",
+            Operation::Delete => "delete",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    PerOp,
+    Batched,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 2] = [Mode::PerOp, Mode::Batched];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Mode::PerOp => "per-op",
+            Mode::Batched => "batched",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    StatsCsv,
+    Json,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Csv, Format::StatsCsv, Format::Json];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::StatsCsv => "stats-csv",
+            Format::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub size: usize,
+    pub runs: u64,
+    pub seed: u64,
+    pub heaps: Vec<HeapKind>,
+    pub operations: Vec<Operation>,
+    pub mode: Mode,
+    pub batch_size: usize,
+    pub bucket_size: usize,
+    pub output: Option<PathBuf>,
+    pub format: Format,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            size: 1000000,
+            runs: 15,
+            seed: 131254153214,
+            heaps: HeapKind::ALL.to_vec(),
+            operations: Operation::ALL.to_vec(),
+            mode: Mode::PerOp,
+            batch_size: 100,
+            bucket_size: 10000,
+            output: None,
+            format: Format::Csv,
+        }
+    }
+}
+
+impl Options {
+    pub fn batch_size(&self) -> usize {
+        match self.mode {
+            Mode::PerOp => 1,
+            Mode::Batched => self.batch_size,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CompareOptions {
+    pub baseline: PathBuf,
+    pub candidate: PathBuf,
+    pub threshold: f64,
+    pub confidence: f64,
+    pub resamples: usize,
+    pub seed: u64,
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Run(Options),
+    Compare(CompareOptions),
+    Help,
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+    if args.peek().is_some_and(|arg| arg == "compare") {
+        args.next();
+        return parse_compare(args);
+    }
+
+    let mut options = Options::default();
+
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg, None),
+        };
+
+        if flag == "-h" || flag == "--help" {
+            return Ok(Command::Help);
+        }
+
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("missing value for {flag}"))
+        };
+
+        match flag.as_str() {
+            "-n" | "--size" => options.size = parse_number(&flag, &value()?)?,
+            "-r" | "--runs" => options.runs = parse_number(&flag, &value()?)?,
+            "-s" | "--seed" => options.seed = parse_number(&flag, &value()?)?,
+            "--heaps" => {
+                options.heaps = parse_list(&flag, &value()?, &HeapKind::ALL, |kind| kind.label())?
+            }
+            "--operations" => {
+                options.operations = parse_list(&flag, &value()?, &Operation::ALL, |operation| {
+                    operation.label()
+                })?
+            }
+            "--mode" => {
+                options.mode = parse_choice(&flag, &value()?, &Mode::ALL, |mode| mode.label())?
+            }
+            "--batch-size" => options.batch_size = parse_number(&flag, &value()?)?,
+            "--bucket-size" => options.bucket_size = parse_number(&flag, &value()?)?,
+            "-o" | "--output" => options.output = Some(PathBuf::from(value()?)),
+            "--format" => {
+                options.format =
+                    parse_choice(&flag, &value()?, &Format::ALL, |format| format.label())?
+            }
+            _ => return Err(format!("unknown argument {flag}")),
+        }
+    }
+
+    if options.batch_size == 0 || options.bucket_size == 0 {
+        return Err("batch and bucket sizes must be positive".to_string());
+    }
+
+    if options.mode == Mode::Batched && options.bucket_size % options.batch_size != 0 {
+        return Err("bucket size must be a multiple of the batch size".to_string());
+    }
+
+    Ok(Command::Run(options))
+}
+
+fn parse_compare(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut threshold: f64 = 5.0;
+    let mut confidence: f64 = 0.95;
+    let mut resamples = 2000;
+    let mut seed = Options::default().seed;
+    let mut files = Vec::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg, None),
+        };
+
+        if flag == "-h" || flag == "--help" {
+            return Ok(Command::Help);
+        }
+
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("missing value for {flag}"))
+        };
+
+        match flag.as_str() {
+            "-t" | "--threshold" => threshold = parse_number(&flag, &value()?)?,
+            "--confidence" => confidence = parse_number(&flag, &value()?)?,
+            "--resamples" => resamples = parse_number(&flag, &value()?)?,
+            "-s" | "--seed" => seed = parse_number(&flag, &value()?)?,
+            _ if flag.starts_with('-') => return Err(format!("unknown argument {flag}")),
+            _ => files.push(PathBuf::from(flag)),
+        }
+    }
+
+    let [baseline, candidate] = <[PathBuf; 2]>::try_from(files)
+        .map_err(|_| "compare takes a baseline and a candidate file".to_string())?;
+
+    if threshold.is_nan() || threshold < 0.0 {
+        return Err("threshold must not be negative".to_string());
+    }
+
+    if confidence.is_nan() || confidence <= 0.0 || confidence >= 1.0 {
+        return Err("confidence must lie between 0 and 1".to_string());
+    }
+
+    if resamples == 0 {
+        return Err("resamples must be positive".to_string());
+    }
+
+    Ok(Command::Compare(CompareOptions {
+        baseline,
+        candidate,
+        threshold,
+        confidence,
+        resamples,
+        seed,
+    }))
+}
+
+fn parse_number<T: FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value {value:?} for {flag}"))
+}
+
+fn parse_choice<T: Copy>(
+    flag: &str,
+    value: &str,
+    choices: &[T],
+    label: impl Fn(T) -> &'static str,
+) -> Result<T, String> {
+    choices
+        .iter()
+        .copied()
+        .find(|&choice| label(choice) == value)
+        .ok_or_else(|| format!("invalid value {value:?} for {flag}"))
+}
+
+fn parse_list<T: Copy + PartialEq>(
+    flag: &str,
+    value: &str,
+    choices: &[T],
+    label: impl Fn(T) -> &'static str,
+) -> Result<Vec<T>, String> {
+    let mut list = Vec::new();
+    for item in value.split(',') {
+        let choice = parse_choice(flag, item.trim(), choices, &label)?;
+        if !list.contains(&choice) {
+            list.push(choice);
+        }
+    }
+
+    Ok(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{parse, Command, CompareOptions, Format, HeapKind, Mode, Operation, Options};
+
+    fn run(args: &[&str]) -> Result<Options, String> {
+        match parse(args.iter().map(|arg| arg.to_string()))? {
+            Command::Run(options) => Ok(options),
+            command => panic!("expected a run, got {command:?}"),
+        }
+    }
+
+    fn compare(args: &[&str]) -> Result<CompareOptions, String> {
+        match parse(args.iter().map(|arg| arg.to_string()))? {
+            Command::Compare(options) => Ok(options),
+            command => panic!("expected a comparison, got {command:?}"),
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let options = run(&[]).unwrap();
+        assert_eq!(options.size, 1000000);
+        assert_eq!(options.runs, 15);
+        assert_eq!(options.heaps, HeapKind::ALL);
+        assert_eq!(options.operations, Operation::ALL);
+        assert_eq!(options.mode, Mode::PerOp);
+        assert_eq!(options.batch_size(), 1);
+        assert_eq!(options.format, Format::Csv);
+        assert_eq!(options.output, None);
+    }
+
+    #[test]
+    fn separate_and_inline_values() {
+        let options = run(&[
+            "-n",
+            "500",
+            "--runs=3",
+            "-s",
+            "7",
+            "--mode=batched",
+            "--batch-size",
+            "25",
+            "--bucket-size=100",
+            "-o",
+            "out.json",
+            "--format=json",
+        ])
+        .unwrap();
+
+        assert_eq!(options.size, 500);
+        assert_eq!(options.runs, 3);
+        assert_eq!(options.seed, 7);
+        assert_eq!(options.mode, Mode::Batched);
+        assert_eq!(options.batch_size(), 25);
+        assert_eq!(options.bucket_size, 100);
+        assert_eq!(options.output, Some(PathBuf::from("out.json")));
+        assert_eq!(options.format, Format::Json);
+    }
+
+    #[test]
+    fn lists_drop_duplicates_and_keep_order() {
+        let options = run(&[
+            "--heaps",
+            "pairing, 4-ary,pairing,binary",
+            "--operations=delete",
+        ])
+        .unwrap();
+        assert_eq!(
+            options.heaps,
+            [HeapKind::Pairing, HeapKind::Quaternary, HeapKind::Binary]
+        );
+        assert_eq!(options.operations, [Operation::Delete]);
+
+        assert!(run(&["--heaps", "binary,ternary"]).is_err());
+        assert!(run(&["--heaps", ""]).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert_eq!(run(&["-n"]).unwrap_err(), "missing value for -n");
+        assert_eq!(run(&["--seed"]).unwrap_err(), "missing value for --seed");
+        assert_eq!(run(&["--bogus"]).unwrap_err(), "unknown argument --bogus");
+        assert_eq!(run(&["-x=1"]).unwrap_err(), "unknown argument -x=1");
+        assert!(run(&["--size", "-5"]).is_err());
+        assert!(run(&["--runs=many"]).is_err());
+        assert!(run(&["--mode", "sometimes"]).is_err());
+        assert!(run(&["--format", "xml"]).is_err());
+    }
+
+    #[test]
+    fn buckets_hold_whole_batches() {
+        assert!(run(&["--mode=batched", "--batch-size=30", "--bucket-size=100"]).is_err());
+        assert!(run(&["--mode=batched", "--batch-size=0"]).is_err());
+        assert!(run(&["--bucket-size=0"]).is_err());
+        assert!(run(&["--mode=batched", "--batch-size=20", "--bucket-size=100"]).is_ok());
+
+        // The batch size only matters in batched mode.
+        assert!(run(&["--batch-size=30", "--bucket-size=100"]).is_ok());
+    }
+
+    #[test]
+    fn help() {
+        for args in [&["-h"][..], &["--size", "5", "--help"], &["compare", "-h"]] {
+            let command = parse(args.iter().map(|arg| arg.to_string())).unwrap();
+            assert!(matches!(command, Command::Help));
+        }
+    }
+
+    #[test]
+    fn compare_options() {
+        let options = compare(&["compare", "old.csv", "--threshold=2.5", "new.json"]).unwrap();
+        assert_eq!(options.baseline, PathBuf::from("old.csv"));
+        assert_eq!(options.candidate, PathBuf::from("new.json"));
+        assert_eq!(options.threshold, 2.5);
+        assert_eq!(options.confidence, 0.95);
+        assert_eq!(options.resamples, 2000);
+
+        let options = compare(&[
+            "compare",
+            "--confidence",
+            "0.9",
+            "--resamples=50",
+            "-s",
+            "9",
+            "a",
+            "b",
+        ])
+        .unwrap();
+        assert_eq!(options.confidence, 0.9);
+        assert_eq!(options.resamples, 50);
+        assert_eq!(options.seed, 9);
+    }
+
+    #[test]
+    fn compare_validation() {
+        for args in [
+            &["compare"][..],
+            &["compare", "a"],
+            &["compare", "a", "b", "c"],
+            &["compare", "a", "b", "--threshold=-1"],
+            &["compare", "a", "b", "--threshold=NaN"],
+            &["compare", "a", "b", "--confidence=0"],
+            &["compare", "a", "b", "--confidence=1"],
+            &["compare", "a", "b", "--resamples=0"],
+            &["compare", "a", "b", "--size=5"],
+            &["compare", "a", "b", "--seed"],
+        ] {
+            assert!(compare(args).is_err(), "{args:?} was accepted");
+        }
+    }
+}
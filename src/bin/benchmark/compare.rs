@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::cli::CompareOptions;
+use crate::results::{self, Saved};
+
+/// Bucket means of one heap and operation, grouped by bucket index so that resampling keeps the
+/// mix of heap sizes of the original runs.
+type Strata = BTreeMap<usize, Vec<f64>>;
+
+/// Prints the speedup of the candidate over the baseline for every heap and operation found in
+/// both files. Returns whether any of them regressed past the threshold.
+pub fn compare(options: &CompareOptions) -> Result<bool, String> {
+    let baseline = results::load(&options.baseline)?;
+    let candidate = results::load(&options.candidate)?;
+
+    for name in ["size", "mode", "batch_size", "bucket_size", "profile"] {
+        let (old, new) = (baseline.header(name), candidate.header(name));
+        if old != new {
+            eprintln!(
+                "warning: {name} differs: {} in the baseline, {} in the candidate",
+                old.unwrap_or("nothing"),
+                new.unwrap_or("nothing")
+            );
+        }
+    }
+
+    let baseline = strata(&baseline);
+    let candidate = strata(&candidate);
+
+    for (heap, operation) in candidate.keys() {
+        if !baseline.contains_key(&(heap.clone(), operation.clone())) {
+            eprintln!("warning: {heap} {operation} only appears in the candidate");
+        }
+    }
+
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let limit = 1.0 / (1.0 + options.threshold / 100.0);
+    let tail = (1.0 - options.confidence) / 2.0;
+    let mut regressed = false;
+
+    println!(
+        "{:<12} {:<10} {:>12} {:>12} {:>8}  {:<18} status",
+        "heap",
+        "operation",
+        "baseline ns",
+        "candidate ns",
+        "speedup",
+        format!("{}% interval", options.confidence * 100.0)
+    );
+
+    for ((heap, operation), old) in &baseline {
+        let Some(new) = candidate.get(&(heap.clone(), operation.clone())) else {
+            eprintln!("warning: {heap} {operation} is missing from the candidate");
+            continue;
+        };
+
+        let (old_mean, new_mean) = (mean(old), mean(new));
+        let mut speedups = (0..options.resamples)
+            .map(|_| resample(old, &mut rng) / resample(new, &mut rng))
+            .collect::<Vec<_>>();
+        speedups.sort_by(f64::total_cmp);
+        let low = quantile(&speedups, tail);
+        let high = quantile(&speedups, 1.0 - tail);
+
+        let status = if high < limit {
+            regressed = true;
+            "regression"
+        } else if high < 1.0 {
+            "slower"
+        } else if low > 1.0 {
+            "faster"
+        } else {
+            ""
+        };
+
+        println!(
+            "{heap:<12} {operation:<10} {old_mean:>12.1} {new_mean:>12.1} {:>7.3}x  {:<18} {status}",
+            old_mean / new_mean,
+            format!("[{low:.3}, {high:.3}]")
+        );
+    }
+
+    Ok(regressed)
+}
+
+fn strata(saved: &Saved) -> BTreeMap<(String, String), Strata> {
+    let mut strata = BTreeMap::<_, Strata>::new();
+    for bucket in &saved.buckets {
+        strata
+            .entry((bucket.heap.clone(), bucket.operation.clone()))
+            .or_default()
+            .entry(bucket.n)
+            .or_default()
+            .push(bucket.mean);
+    }
+
+    strata
+}
+
+fn mean(strata: &Strata) -> f64 {
+    let (sum, count) = strata
+        .values()
+        .flatten()
+        .fold((0.0, 0), |(sum, count), mean| (sum + mean, count + 1));
+
+    sum / count as f64
+}
+
+/// Mean of a bootstrap resample drawn separately within every bucket index.
+fn resample(strata: &Strata, rng: &mut StdRng) -> f64 {
+    let mut sum = 0.0;
+    let mut count = 0;
+    for means in strata.values() {
+        for _ in means {
+            sum += means[rng.gen_range(0..means.len())];
+        }
+        count += means.len();
+    }
+
+    sum / count as f64
+}
+
+fn quantile(sorted: &[f64], fraction: f64) -> f64 {
+    sorted[(fraction * (sorted.len() - 1) as f64).round() as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{compare, quantile, resample, Strata};
+    use crate::cli::CompareOptions;
+
+    #[test]
+    fn quantile_rounds_to_the_nearest_index() {
+        let sorted = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(quantile(&sorted, 0.0), 1.0);
+        assert_eq!(quantile(&sorted, 0.3), 2.0);
+        assert_eq!(quantile(&sorted, 0.5), 3.0);
+        assert_eq!(quantile(&sorted, 0.9), 5.0);
+        assert_eq!(quantile(&sorted, 1.0), 5.0);
+        assert_eq!(quantile(&[7.0], 0.025), 7.0);
+    }
+
+    #[test]
+    fn resample_draws_within_each_bucket() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        // Every bucket keeps its own weight, whatever gets drawn inside it.
+        let strata = Strata::from([(0, vec![1.0, 1.0, 1.0]), (1, vec![10.0])]);
+        for _ in 0..100 {
+            assert_eq!(resample(&strata, &mut rng), 3.25);
+        }
+
+        let strata = Strata::from([(0, vec![1.0, 3.0])]);
+        let draws = (0..100)
+            .map(|_| resample(&strata, &mut rng))
+            .collect::<Vec<_>>();
+        assert!(draws.iter().all(|&mean| [1.0, 2.0, 3.0].contains(&mean)));
+        assert!(draws.contains(&1.0) && draws.contains(&3.0));
+    }
+
+    /// Writes a stats CSV of 15 runs over 4 buckets, each bucket mean scaled by `factor` and
+    /// perturbed by up to 3% of noise.
+    fn results(name: &str, factor: f64, seed: u64) -> PathBuf {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut text = String::from("# seed=1\n# mode=batched\n");
+        text.push_str("run,seed,heap,operation,n,samples,mean,median,p99,max\n");
+        for run in 0..15 {
+            for n in 0..4 {
+                let mean = (100.0 + 10.0 * n as f64) * factor * rng.gen_range(0.97..1.03);
+                writeln!(text, "{run},{run},binary,insert,{n},100,{mean:.1},0,0,0").unwrap();
+            }
+        }
+
+        let path = std::env::temp_dir().join(format!("heaps-compare-{}-{name}.csv", process::id()));
+        fs::write(&path, text).unwrap();
+
+        path
+    }
+
+    fn regressed(candidate_factor: f64, name: &str) -> bool {
+        let options = CompareOptions {
+            baseline: results(&format!("{name}-baseline"), 1.0, 1),
+            candidate: results(&format!("{name}-candidate"), candidate_factor, 2),
+            threshold: 5.0,
+            confidence: 0.95,
+            resamples: 2000,
+            seed: 3,
+        };
+
+        let regressed = compare(&options).unwrap();
+        fs::remove_file(&options.baseline).unwrap();
+        fs::remove_file(&options.candidate).unwrap();
+
+        regressed
+    }
+
+    #[test]
+    fn flags_only_clear_regressions() {
+        assert!(regressed(1.5, "slower"));
+        assert!(regressed(1.1, "slightly-slower"));
+        assert!(!regressed(1.0, "noise"));
+        assert!(!regressed(1.02, "within-threshold"));
+        assert!(!regressed(0.5, "faster"));
+    }
+
+    #[test]
+    fn reports_unreadable_files() {
+        let options = CompareOptions {
+            baseline: PathBuf::from("/nonexistent/baseline.csv"),
+            candidate: PathBuf::from("/nonexistent/candidate.csv"),
+            threshold: 5.0,
+            confidence: 0.95,
+            resamples: 10,
+            seed: 3,
+        };
+        assert!(compare(&options).is_err());
+    }
+}
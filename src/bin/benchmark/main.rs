@@ -0,0 +1,184 @@
+mod cli;
+mod compare;
+mod json;
+mod results;
+mod stats;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process;
+use std::time::{Duration, Instant};
+
+use heaps::binomial::BinomialHeap;
+use heaps::dary::{BinaryHeap, DaryHeap};
+use heaps::fibonacci::FibonacciHeap;
+use heaps::leftist::LeftistHeap;
+use heaps::pairing::PairingHeap;
+use heaps::randomized::RandomizedMeldableHeap;
+use heaps::skew::SkewHeap;
+use heaps::{Heap, HeapEntry};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use cli::{Command, HeapKind, Operation, Options};
+use results::{Bucket, Writer};
+use stats::Summary;
+
+trait BenchHeap {
+    /// Inserts `keys`, or deletes one minimum per slot of `deleted`, and returns how long it took.
+    /// The clock runs inside the implementation, which is generic over the heap, so the virtual
+    /// call that picks the heap stays outside the timed region.
+    fn time(&mut self, operation: Operation, keys: &[usize], deleted: &mut [usize]) -> Duration;
+}
+
+impl<H: Heap<usize, ()>> BenchHeap for H {
+    fn time(&mut self, operation: Operation, keys: &[usize], deleted: &mut [usize]) -> Duration {
+        match operation {
+            Operation::Insert => {
+                let start = Instant::now();
+                for &key in keys {
+                    self.insert(HeapEntry { key, data: () });
+                }
+                start.elapsed()
+            }
+            Operation::Delete => {
+                let start = Instant::now();
+                for key in deleted {
+                    *key = self.delete_min().unwrap().key;
+                }
+                start.elapsed()
+            }
+        }
+    }
+}
+
+fn build_heap(kind: HeapKind, seed: u64) -> Box<dyn BenchHeap> {
+    match kind {
+        HeapKind::Binary => Box::new(BinaryHeap::new()),
+        HeapKind::Binomial => Box::new(BinomialHeap::new()),
+        HeapKind::Randomized => Box::new(RandomizedMeldableHeap::with_seed(seed)),
+        HeapKind::Pairing => Box::new(PairingHeap::new()),
+        HeapKind::Fibonacci => Box::new(FibonacciHeap::new()),
+        HeapKind::Quaternary => Box::new(DaryHeap::<_, _, 4>::new()),
+        HeapKind::Octonary => Box::new(DaryHeap::<_, _, 8>::new()),
+        HeapKind::Leftist => Box::new(LeftistHeap::new()),
+        HeapKind::Skew => Box::new(SkewHeap::new()),
+    }
+}
+
+fn main() {
+    let options = match cli::parse(env::args().skip(1)) {
+        Ok(Command::Run(options)) => options,
+        Ok(Command::Compare(options)) => match compare::compare(&options) {
+            Ok(regressed) => process::exit(i32::from(regressed)),
+            Err(message) => {
+                eprintln!("error: {message}");
+                process::exit(2);
+            }
+        },
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(message) => {
+            eprintln!("error: {message}\n\n{}", cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    let output: Box<dyn Write> = match &options.output {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(file),
+            Err(error) => {
+                eprintln!("error: cannot create {}: {error}", path.display());
+                process::exit(1);
+            }
+        },
+        None => Box::new(io::stdout().lock()),
+    };
+
+    if let Err(error) = run(&options, Writer::new(BufWriter::new(output), &options)) {
+        eprintln!("error: {error}");
+        process::exit(1);
+    }
+}
+
+fn run(options: &Options, mut writer: Writer<impl Write>) -> io::Result<()> {
+    writer.begin(options)?;
+
+    for run in 0..options.runs {
+        test(options, run, &mut writer)?;
+    }
+
+    writer.finish()
+}
+
+fn test(options: &Options, run: u64, writer: &mut Writer<impl Write>) -> io::Result<()> {
+    let seed = options.seed.wrapping_add(run);
+    let mut vals = (0..options.size).collect::<Vec<_>>();
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    vals.shuffle(&mut rng);
+
+    let mut heaps = options
+        .heaps
+        .iter()
+        .map(|&kind| (kind, build_heap(kind, seed)))
+        .collect::<Vec<_>>();
+    let mut times = vec![0; heaps.len()];
+    let mut deleted = vec![vec![0; options.batch_size()]; heaps.len()];
+    let mut samples = vec![Vec::new(); heaps.len()];
+
+    for operation in Operation::ALL {
+        let report = options.operations.contains(&operation);
+        for (n, bucket) in vals.chunks(options.bucket_size).enumerate() {
+            for batch in bucket.chunks(options.batch_size()) {
+                for (((_, heap), time), deleted) in
+                    heaps.iter_mut().zip(&mut times).zip(&mut deleted)
+                {
+                    *time = heap
+                        .time(operation, batch, &mut deleted[..batch.len()])
+                        .as_nanos();
+                }
+
+                if operation == Operation::Delete {
+                    for ((kind, _), keys) in heaps.iter().zip(&deleted) {
+                        assert_eq!(
+                            deleted[0][..batch.len()],
+                            keys[..batch.len()],
+                            "{} disagrees with {}",
+                            kind.label(),
+                            heaps[0].0.label()
+                        );
+                    }
+                }
+
+                if report {
+                    for (((kind, _), samples), &time) in heaps.iter().zip(&mut samples).zip(&times)
+                    {
+                        writer.sample(*kind, operation, n, time)?;
+                        samples.push(time as f64 / batch.len() as f64);
+                    }
+                }
+            }
+
+            if report {
+                for ((kind, _), samples) in heaps.iter().zip(&mut samples) {
+                    writer.bucket(&Bucket {
+                        run,
+                        seed,
+                        heap: *kind,
+                        operation,
+                        n,
+                        summary: Summary::of(samples),
+                    })?;
+                    samples.clear();
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
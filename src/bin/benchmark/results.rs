@@ -0,0 +1,506 @@
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::thread;
+
+use crate::cli::{Format, HeapKind, Mode, Operation, Options};
+use crate::json::{self, Json};
+use crate::stats::Summary;
+
+pub struct Bucket {
+    pub run: u64,
+    pub seed: u64,
+    pub heap: HeapKind,
+    pub operation: Operation,
+    pub n: usize,
+    pub summary: Summary,
+}
+
+/// Writes results in the requested format. The plain CSV keeps the original stream of rows;
+/// the stats CSV and JSON formats start with a header describing the run and then hold one
+/// record per bucket.
+pub struct Writer<W> {
+    output: W,
+    format: Format,
+    mode: Mode,
+    first: bool,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(output: W, options: &Options) -> Writer<W> {
+        Writer {
+            output,
+            format: options.format,
+            mode: options.mode,
+            first: true,
+        }
+    }
+
+    pub fn begin(&mut self, options: &Options) -> io::Result<()> {
+        let metadata = metadata(options);
+        let out = &mut self.output;
+
+        match (self.format, self.mode) {
+            (Format::Csv, Mode::PerOp) => writeln!(out, "heap,operation,n,time"),
+            (Format::Csv, Mode::Batched) => {
+                writeln!(out, "run,heap,operation,n,mean,median,p99,max")
+            }
+            (Format::StatsCsv, _) => {
+                for (name, value) in &metadata {
+                    writeln!(out, "# {name}={}", value.plain())?;
+                }
+                for kind in &options.heaps {
+                    match kind.arity() {
+                        Some(arity) => writeln!(out, "# heap={} arity={arity}", kind.label())?,
+                        None => writeln!(out, "# heap={}", kind.label())?,
+                    }
+                }
+                writeln!(out, "run,seed,heap,operation,n,samples,mean,median,p99,max")
+            }
+            (Format::Json, _) => {
+                writeln!(out, "{{")?;
+                writeln!(out, "  \"header\": {{")?;
+                for (name, value) in &metadata {
+                    writeln!(out, "    \"{name}\": {},", value.json())?;
+                }
+                write!(out, "    \"heaps\": [")?;
+                for (i, kind) in options.heaps.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { ", " };
+                    match kind.arity() {
+                        Some(arity) => write!(
+                            out,
+                            "{separator}{{\"name\": \"{}\", \"arity\": {arity}}}",
+                            kind.label()
+                        )?,
+                        None => write!(out, "{separator}{{\"name\": \"{}\"}}", kind.label())?,
+                    }
+                }
+                writeln!(out, "]")?;
+                writeln!(out, "  }},")?;
+                write!(out, "  \"buckets\": [")
+            }
+        }
+    }
+
+    /// Records the time of a single operation. Only the plain CSV in per-op mode prints these.
+    pub fn sample(
+        &mut self,
+        heap: HeapKind,
+        operation: Operation,
+        n: usize,
+        time: u128,
+    ) -> io::Result<()> {
+        match (self.format, self.mode) {
+            (Format::Csv, Mode::PerOp) => writeln!(
+                self.output,
+                "{},{},{n},{time}",
+                heap.label(),
+                operation.label()
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn bucket(&mut self, bucket: &Bucket) -> io::Result<()> {
+        let Bucket {
+            run,
+            seed,
+            heap,
+            operation,
+            n,
+            summary,
+        } = bucket;
+        let out = &mut self.output;
+
+        match (self.format, self.mode) {
+            (Format::Csv, Mode::PerOp) => Ok(()),
+            (Format::Csv, Mode::Batched) => writeln!(
+                out,
+                "{run},{},{},{n},{:.1},{:.1},{:.1},{:.1}",
+                heap.label(),
+                operation.label(),
+                summary.mean,
+                summary.median,
+                summary.p99,
+                summary.max
+            ),
+            (Format::StatsCsv, _) => writeln!(
+                out,
+                "{run},{seed},{},{},{n},{},{:.1},{:.1},{:.1},{:.1}",
+                heap.label(),
+                operation.label(),
+                summary.samples,
+                summary.mean,
+                summary.median,
+                summary.p99,
+                summary.max
+            ),
+            (Format::Json, _) => {
+                let separator = if self.first { "" } else { "," };
+                self.first = false;
+                write!(
+                    out,
+                    "{separator}\n    {{\"run\": {run}, \"seed\": {seed}, \"heap\": \"{}\", \
+                     \"operation\": \"{}\", \"n\": {n}, \"samples\": {}, \"mean\": {:.1}, \
+                     \"median\": {:.1}, \"p99\": {:.1}, \"max\": {:.1}}}",
+                    heap.label(),
+                    operation.label(),
+                    summary.samples,
+                    summary.mean,
+                    summary.median,
+                    summary.p99,
+                    summary.max
+                )
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.format == Format::Json {
+            writeln!(self.output, "\n  ]\n}}")?;
+        }
+
+        self.output.flush()
+    }
+}
+
+enum Value {
+    Text(String),
+    Number(u64),
+}
+
+impl Value {
+    fn json(&self) -> String {
+        match self {
+            Value::Text(text) => quote(text),
+            Value::Number(number) => number.to_string(),
+        }
+    }
+
+    fn plain(&self) -> String {
+        match self {
+            Value::Text(text) => text.clone(),
+            Value::Number(number) => number.to_string(),
+        }
+    }
+}
+
+fn metadata(options: &Options) -> Vec<(&'static str, Value)> {
+    let profile = if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    };
+
+    vec![
+        ("revision", Value::Text(env!("GIT_REVISION").to_string())),
+        ("profile", Value::Text(profile.to_string())),
+        ("arch", Value::Text(env::consts::ARCH.to_string())),
+        ("os", Value::Text(env::consts::OS.to_string())),
+        ("host", Value::Text(hostname())),
+        ("cpu", Value::Text(cpu_model())),
+        ("cpus", Value::Number(cpus())),
+        ("seed", Value::Number(options.seed)),
+        ("runs", Value::Number(options.runs)),
+        ("size", Value::Number(options.size as u64)),
+        ("mode", Value::Text(options.mode.label().to_string())),
+        ("batch_size", Value::Number(options.batch_size() as u64)),
+        ("bucket_size", Value::Number(options.bucket_size as u64)),
+    ]
+}
+
+/// Falls back to `unknown` where neither the kernel nor the environment names the machine.
+fn hostname() -> String {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| fs::read_to_string("/etc/hostname"))
+        .ok()
+        .or_else(|| env::var("COMPUTERNAME").ok())
+        .or_else(|| env::var("HOSTNAME").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// The model name from `/proc/cpuinfo`, which only Linux has.
+fn cpu_model() -> String {
+    fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|cpuinfo| {
+            cpuinfo.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                (name.trim() == "model name").then(|| value.trim().to_string())
+            })
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn cpus() -> u64 {
+    thread::available_parallelism().map_or(0, |cpus| cpus.get() as u64)
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+/// A results file read back from disk. Header values are kept as text.
+pub struct Saved {
+    pub header: Vec<(String, String)>,
+    pub buckets: Vec<SavedBucket>,
+}
+
+pub struct SavedBucket {
+    pub heap: String,
+    pub operation: String,
+    pub n: usize,
+    pub mean: f64,
+}
+
+impl Saved {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.header
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Reads a file written with `--format stats-csv` or `--format json`.
+pub fn load(path: &Path) -> Result<Saved, String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("{}: {error}", path.display()))?;
+
+    let saved = if text.trim_start().starts_with('{') {
+        load_json(&text)
+    } else {
+        load_csv(&text)
+    };
+
+    saved.map_err(|message| format!("{}: {message}", path.display()))
+}
+
+fn load_csv(text: &str) -> Result<Saved, String> {
+    let mut header = Vec::new();
+    let mut lines = text.lines().enumerate();
+
+    let columns = loop {
+        let Some((_, line)) = lines.next() else {
+            return Err("missing column names".to_string());
+        };
+
+        match line.strip_prefix("# ") {
+            Some(entry) => {
+                let (name, value) = entry
+                    .split_once('=')
+                    .ok_or_else(|| format!("malformed header line {line:?}"))?;
+                header.push((name.to_string(), value.to_string()));
+            }
+            None => break line.split(',').collect::<Vec<_>>(),
+        }
+    };
+
+    if header.is_empty() {
+        return Err("no run header; write results with --format stats-csv or json".to_string());
+    }
+
+    let column = |name: &str| {
+        columns
+            .iter()
+            .position(|column| *column == name)
+            .ok_or_else(|| format!("missing column {name}"))
+    };
+    let [heap, operation, n, mean] = [
+        column("heap")?,
+        column("operation")?,
+        column("n")?,
+        column("mean")?,
+    ];
+
+    let mut buckets = Vec::new();
+    for (number, line) in lines {
+        if line.is_empty() {
+            continue;
+        }
+
+        let fields = line.split(',').collect::<Vec<_>>();
+        let field = |index: usize| {
+            fields
+                .get(index)
+                .copied()
+                .ok_or_else(|| format!("line {}: too few fields", number + 1))
+        };
+        let invalid = || format!("line {}: invalid number", number + 1);
+
+        buckets.push(SavedBucket {
+            heap: field(heap)?.to_string(),
+            operation: field(operation)?.to_string(),
+            n: field(n)?.parse().map_err(|_| invalid())?,
+            mean: field(mean)?.parse().map_err(|_| invalid())?,
+        });
+    }
+
+    Ok(Saved { header, buckets })
+}
+
+fn load_json(text: &str) -> Result<Saved, String> {
+    let root = json::parse(text)?;
+
+    let header = match root.get("header") {
+        Some(Json::Object(entries)) => entries
+            .iter()
+            .filter_map(|(name, value)| {
+                let value = match value {
+                    Json::String(string) => string.clone(),
+                    Json::Number(number) => number.clone(),
+                    Json::Bool(bool) => bool.to_string(),
+                    _ => return None,
+                };
+                Some((name.clone(), value))
+            })
+            .collect(),
+        _ => return Err("missing run header".to_string()),
+    };
+
+    let Some(Json::Array(records)) = root.get("buckets") else {
+        return Err("missing buckets".to_string());
+    };
+
+    let mut buckets = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        let invalid = || format!("bucket {index}: missing or invalid fields");
+        let number = |name| record.get(name).and_then(Json::as_f64).ok_or_else(invalid);
+        let string = |name| {
+            record
+                .get(name)
+                .and_then(Json::as_str)
+                .map(str::to_string)
+                .ok_or_else(invalid)
+        };
+
+        buckets.push(SavedBucket {
+            heap: string("heap")?,
+            operation: string("operation")?,
+            n: number("n")? as usize,
+            mean: number("mean")?,
+        });
+    }
+
+    Ok(Saved { header, buckets })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_csv, load_json, Bucket, Saved, Writer};
+    use crate::cli::{Format, HeapKind, Mode, Operation, Options};
+    use crate::stats::Summary;
+
+    fn write(format: Format) -> String {
+        let options = Options {
+            format,
+            mode: Mode::Batched,
+            heaps: vec![HeapKind::Quaternary, HeapKind::Pairing],
+            ..Options::default()
+        };
+
+        let mut output = Vec::new();
+        let mut writer = Writer::new(&mut output, &options);
+        writer.begin(&options).unwrap();
+        for (run, heap, n) in [
+            (0, HeapKind::Quaternary, 0),
+            (0, HeapKind::Pairing, 0),
+            (1, HeapKind::Quaternary, 7),
+        ] {
+            writer
+                .bucket(&Bucket {
+                    run,
+                    seed: options.seed + run,
+                    heap,
+                    operation: Operation::Delete,
+                    n,
+                    summary: Summary::of(&mut [1.0, 2.0, 4.5 + n as f64]),
+                })
+                .unwrap();
+        }
+        writer.finish().unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    fn check(saved: Saved) {
+        assert_eq!(saved.header("seed"), Some("131254153214"));
+        assert_eq!(saved.header("mode"), Some("batched"));
+        assert_eq!(saved.header("batch_size"), Some("100"));
+        assert!(saved.header("host").is_some_and(|host| !host.is_empty()));
+        assert!(saved.header("cpu").is_some());
+        assert!(saved
+            .header("cpus")
+            .is_some_and(|cpus| cpus.parse::<u64>().is_ok()));
+        assert_eq!(saved.header("missing"), None);
+
+        let buckets = saved
+            .buckets
+            .iter()
+            .map(|bucket| {
+                (
+                    bucket.heap.as_str(),
+                    bucket.operation.as_str(),
+                    bucket.n,
+                    bucket.mean,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            buckets,
+            [
+                ("4-ary", "delete", 0, 2.5),
+                ("pairing", "delete", 0, 2.5),
+                ("4-ary", "delete", 7, 4.8)
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_back_stats_csv() {
+        let text = write(Format::StatsCsv);
+        assert!(text.contains("# heap=4-ary arity=4\n# heap=pairing\n"));
+        check(load_csv(&text).unwrap());
+    }
+
+    #[test]
+    fn reads_back_json() {
+        check(load_json(&write(Format::Json)).unwrap());
+    }
+
+    #[test]
+    fn batched_csv_rows_name_their_run() {
+        let text = write(Format::Csv);
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "run,heap,operation,n,mean,median,p99,max",
+                "0,4-ary,delete,0,2.5,2.0,4.5,4.5",
+                "0,pairing,delete,0,2.5,2.0,4.5,4.5",
+                "1,4-ary,delete,7,4.8,2.0,11.5,11.5",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_files_without_a_header() {
+        assert!(load_csv(&write(Format::Csv)).is_err());
+        assert!(load_csv("").is_err());
+        assert!(load_json("{\"buckets\": []}").is_err());
+        assert!(load_json("{\"header\": {}, \"buckets\": [{\"heap\": \"binary\"}]}").is_err());
+    }
+}
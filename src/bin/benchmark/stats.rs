@@ -0,0 +1,81 @@
+#[derive(Debug, Clone, Copy)]
+pub struct Summary {
+    pub samples: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl Summary {
+    /// Summarizes a non-empty set of samples, sorting them in place.
+    pub fn of(samples: &mut [f64]) -> Summary {
+        samples.sort_by(f64::total_cmp);
+
+        let len = samples.len();
+        let median = if len % 2 == 1 {
+            samples[len / 2]
+        } else {
+            (samples[len / 2 - 1] + samples[len / 2]) / 2.0
+        };
+
+        Summary {
+            samples: len,
+            mean: samples.iter().sum::<f64>() / len as f64,
+            median,
+            p99: percentile(samples, 0.99),
+            max: samples[len - 1],
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    let rank = (fraction * sorted.len() as f64).ceil() as usize;
+    sorted[rank.max(1) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Summary;
+
+    #[test]
+    fn odd_number_of_samples() {
+        let summary = Summary::of(&mut [5.0, 1.0, 3.0]);
+        assert_eq!(summary.samples, 3);
+        assert_eq!(summary.mean, 3.0);
+        assert_eq!(summary.median, 3.0);
+        assert_eq!(summary.p99, 5.0);
+        assert_eq!(summary.max, 5.0);
+    }
+
+    #[test]
+    fn even_number_of_samples() {
+        let summary = Summary::of(&mut [4.0, 1.0, 3.0, 10.0]);
+        assert_eq!(summary.mean, 4.5);
+        assert_eq!(summary.median, 3.5);
+        assert_eq!(summary.max, 10.0);
+    }
+
+    #[test]
+    fn single_sample() {
+        let summary = Summary::of(&mut [7.0]);
+        assert_eq!(
+            [summary.mean, summary.median, summary.p99, summary.max],
+            [7.0; 4]
+        );
+    }
+
+    #[test]
+    fn p99_is_the_nearest_rank() {
+        // With fewer than 100 samples the nearest rank is the largest one.
+        let mut samples = (1..=50).rev().map(f64::from).collect::<Vec<_>>();
+        assert_eq!(Summary::of(&mut samples).p99, 50.0);
+        let mut samples = (1..=100).map(f64::from).collect::<Vec<_>>();
+        assert_eq!(Summary::of(&mut samples).p99, 99.0);
+        let mut samples = (1..=1000).map(f64::from).collect::<Vec<_>>();
+        assert_eq!(Summary::of(&mut samples).p99, 990.0);
+        let mut samples = (1..=101).map(f64::from).collect::<Vec<_>>();
+        assert_eq!(Summary::of(&mut samples).p99, 100.0);
+    }
+}
@@ -0,0 +1,530 @@
+use std::cmp::Ordering;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+use crate::arena::Arena;
+use crate::compare::{Compare, MinOrder};
+use crate::handle::{Handle, HandleMap};
+use crate::sequence::Sequence;
+use crate::{DecreaseKeyHeap, Heap, HeapEntry, HeapError, MeldableHeap};
+
+#[derive(Debug)]
+struct BinomialNode<K, D> {
+    entry: HeapEntry<K, D>,
+    handle: Handle,
+    seq: u64,
+    parent: Option<usize>,
+    childs: Vec<usize>,
+}
+
+#[derive(Debug)]
+pub struct BinomialHeap<K, D, C = MinOrder> {
+    nodes: Arena<BinomialNode<K, D>>,
+    handles: HandleMap,
+    ranks: Vec<Option<usize>>,
+    min_rank: Option<usize>,
+    sequence: Sequence,
+    compare: C,
+}
+
+impl<K: Ord, D> BinomialHeap<K, D> {
+    pub fn new() -> Self {
+        BinomialHeap::with_compare(MinOrder)
+    }
+
+    pub fn new_stable() -> Self {
+        BinomialHeap::with_compare_stable(MinOrder)
+    }
+
+    pub fn from_vec(entries: Vec<HeapEntry<K, D>>) -> Self {
+        BinomialHeap::from_vec_with_compare(entries, MinOrder)
+    }
+
+    /// Like `from_vec`, with ties kept in the order of `entries`.
+    pub fn from_vec_stable(entries: Vec<HeapEntry<K, D>>) -> Self {
+        BinomialHeap::from_vec_with_compare_stable(entries, MinOrder)
+    }
+}
+
+impl<K, D, C: Compare<K, D>> BinomialHeap<K, D, C> {
+    pub fn with_compare(compare: C) -> Self {
+        BinomialHeap::empty(compare, Sequence::new())
+    }
+
+    pub fn with_compare_stable(compare: C) -> Self {
+        BinomialHeap::empty(compare, Sequence::stable())
+    }
+
+    fn empty(compare: C, sequence: Sequence) -> Self {
+        BinomialHeap {
+            nodes: Arena::new(),
+            handles: HandleMap::new(),
+            ranks: Vec::new(),
+            min_rank: None,
+            sequence,
+            compare,
+        }
+    }
+
+    pub fn from_vec_with_compare(entries: Vec<HeapEntry<K, D>>, compare: C) -> Self {
+        BinomialHeap::build(entries, compare, Sequence::new())
+    }
+
+    pub fn from_vec_with_compare_stable(entries: Vec<HeapEntry<K, D>>, compare: C) -> Self {
+        BinomialHeap::build(entries, compare, Sequence::stable())
+    }
+
+    fn build(entries: Vec<HeapEntry<K, D>>, compare: C, sequence: Sequence) -> Self {
+        let mut heap = BinomialHeap::empty(compare, sequence);
+        let mut trees = entries
+            .into_iter()
+            .map(|entry| heap.add_node(entry))
+            .collect::<Vec<_>>();
+
+        while !trees.is_empty() {
+            heap.ranks.push(if trees.len() % 2 == 1 {
+                trees.pop()
+            } else {
+                None
+            });
+
+            trees = trees
+                .chunks(2)
+                .map(|pair| heap.link(pair[0], pair[1]))
+                .collect();
+        }
+
+        heap.update_min_rank();
+
+        heap
+    }
+
+    fn add_node(&mut self, entry: HeapEntry<K, D>) -> usize {
+        let handle = self.handles.insert(0);
+        let index = self.nodes.insert(BinomialNode {
+            entry,
+            handle,
+            seq: self.sequence.next(),
+            parent: None,
+            childs: Vec::new(),
+        });
+        self.handles.set(handle, index);
+
+        index
+    }
+
+    fn order(&self, a: usize, b: usize) -> Ordering {
+        let (a, b) = (&self.nodes[a], &self.nodes[b]);
+        let ordering = self.compare.compare_entries(&a.entry, &b.entry);
+        self.sequence.tiebreak(ordering, a.seq, b.seq)
+    }
+
+    fn link(&mut self, a: usize, b: usize) -> usize {
+        let (root, child) = if self.order(b, a).is_lt() {
+            (b, a)
+        } else {
+            (a, b)
+        };
+
+        self.nodes[root].childs.push(child);
+        self.nodes[child].parent = Some(root);
+
+        root
+    }
+
+    fn swap_entries(&mut self, a: usize, b: usize) {
+        let (a_node, b_node) = self.nodes.pair_mut(a, b);
+        mem::swap(&mut a_node.entry, &mut b_node.entry);
+        mem::swap(&mut a_node.handle, &mut b_node.handle);
+        mem::swap(&mut a_node.seq, &mut b_node.seq);
+
+        self.handles.set(self.nodes[a].handle, a);
+        self.handles.set(self.nodes[b].handle, b);
+    }
+
+    fn sift_up(&mut self, mut index: usize) {
+        while let Some(parent) = self.nodes[index].parent {
+            if !self.order(index, parent).is_lt() {
+                break;
+            }
+
+            self.swap_entries(index, parent);
+            index = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut index: usize) {
+        while let Some(child) = self.nodes[index]
+            .childs
+            .iter()
+            .copied()
+            .min_by(|&a, &b| self.order(a, b))
+        {
+            if !self.order(child, index).is_lt() {
+                break;
+            }
+
+            self.swap_entries(index, child);
+            index = child;
+        }
+    }
+
+    fn update_min_rank(&mut self) {
+        self.min_rank = self
+            .ranks
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, opt)| opt.map(|v| (idx, v)))
+            .min_by(|a, b| self.order(a.1, b.1))
+            .map(|v| v.0);
+    }
+
+    fn merge_ranks(&mut self, mut other: Vec<Option<usize>>) {
+        let mut ranks = mem::take(&mut self.ranks);
+        if other.len() > ranks.len() {
+            mem::swap(&mut ranks, &mut other);
+        }
+
+        let mut carry_tree = None;
+        let other_rank = other.len();
+        for (rank, other_tree) in other.into_iter().enumerate() {
+            let this_tree = ranks[rank].take();
+            let mut trees = this_tree
+                .into_iter()
+                .chain(other_tree)
+                .chain(carry_tree.take());
+
+            if let Some(first_tree) = trees.next() {
+                if let Some(second_tree) = trees.next() {
+                    carry_tree = Some(self.link(first_tree, second_tree));
+
+                    ranks[rank] = trees.next();
+                } else {
+                    ranks[rank] = Some(first_tree);
+                }
+            }
+        }
+
+        let mut next_rank = other_rank;
+        while let Some(carry) = carry_tree.take() {
+            if let Some(this_tree) = ranks.get_mut(next_rank).and_then(|t| t.take()) {
+                carry_tree = Some(self.link(this_tree, carry));
+            } else if next_rank < ranks.len() {
+                ranks[next_rank] = Some(carry);
+            } else {
+                ranks.push(Some(carry))
+            }
+
+            next_rank += 1;
+        }
+
+        self.ranks = ranks;
+        self.update_min_rank();
+    }
+
+    fn detach_root(&mut self, rank: usize) -> usize {
+        let root = self.ranks[rank].take().unwrap();
+        let childs = mem::take(&mut self.nodes[root].childs);
+        for &child in &childs {
+            self.nodes[child].parent = None;
+        }
+
+        self.merge_ranks(childs.into_iter().map(Some).collect());
+
+        root
+    }
+
+    fn restore_min(&mut self) {
+        if let Some(min_rank) = self.min_rank {
+            let root = self.detach_root(min_rank);
+            self.merge_ranks(vec![Some(root)]);
+        }
+    }
+}
+
+impl<K, D, C: Compare<K, D>> MeldableHeap<K, D> for BinomialHeap<K, D, C> {
+    fn meld(&mut self, mut other: Self) {
+        // Only the nodes of the heap with the smaller arena are moved.
+        let moves_self = self.nodes.slots() < other.nodes.slots();
+        let offset = if moves_self {
+            mem::swap(&mut self.nodes, &mut other.nodes);
+            mem::swap(&mut self.handles, &mut other.handles);
+            mem::swap(&mut self.ranks, &mut other.ranks);
+            self.sequence.append_to(&other.sequence)
+        } else {
+            self.sequence.append(&other.sequence)
+        };
+        let mapping = self.nodes.append(other.nodes);
+        self.handles.append(other.handles);
+        for &index in mapping.iter().flatten() {
+            let node = &mut self.nodes[index];
+            node.seq = node.seq.wrapping_add(offset);
+            node.parent = node.parent.map(|parent| mapping[parent].unwrap());
+            for child in node.childs.iter_mut() {
+                *child = mapping[*child].unwrap();
+            }
+
+            self.handles.set(node.handle, index);
+        }
+
+        let other_ranks = other
+            .ranks
+            .into_iter()
+            .map(|tree| tree.map(|root| mapping[root].unwrap()))
+            .collect();
+        self.merge_ranks(other_ranks);
+    }
+}
+
+impl<K: Ord, D> Default for BinomialHeap<K, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, D, C: Compare<K, D> + Default> FromIterator<HeapEntry<K, D>> for BinomialHeap<K, D, C> {
+    fn from_iter<I: IntoIterator<Item = HeapEntry<K, D>>>(iter: I) -> Self {
+        BinomialHeap::from_vec_with_compare(iter.into_iter().collect(), C::default())
+    }
+}
+
+impl<K, D, C> IntoIterator for BinomialHeap<K, D, C> {
+    type Item = HeapEntry<K, D>;
+    type IntoIter = IntoIter<K, D>;
+
+    fn into_iter(self) -> IntoIter<K, D> {
+        IntoIter {
+            nodes: self.nodes.into_iter(),
+        }
+    }
+}
+
+pub struct IntoIter<K, D> {
+    nodes: <Arena<BinomialNode<K, D>> as IntoIterator>::IntoIter,
+}
+
+impl<K, D> Iterator for IntoIter<K, D> {
+    type Item = HeapEntry<K, D>;
+
+    fn next(&mut self) -> Option<HeapEntry<K, D>> {
+        self.nodes.next().map(|node| node.entry)
+    }
+}
+
+pub struct BinomialPeekMut<'a, K, D, C: Compare<K, D>> {
+    heap: &'a mut BinomialHeap<K, D, C>,
+    modified: bool,
+}
+
+impl<K, D, C: Compare<K, D>> BinomialPeekMut<'_, K, D, C> {
+    fn min_root(&self) -> usize {
+        self.heap.ranks[self.heap.min_rank.unwrap()].unwrap()
+    }
+}
+
+impl<K, D, C: Compare<K, D>> Deref for BinomialPeekMut<'_, K, D, C> {
+    type Target = HeapEntry<K, D>;
+
+    fn deref(&self) -> &HeapEntry<K, D> {
+        &self.heap.nodes[self.min_root()].entry
+    }
+}
+
+impl<K, D, C: Compare<K, D>> DerefMut for BinomialPeekMut<'_, K, D, C> {
+    fn deref_mut(&mut self) -> &mut HeapEntry<K, D> {
+        self.modified = true;
+        let min_root = self.min_root();
+        &mut self.heap.nodes[min_root].entry
+    }
+}
+
+impl<K, D, C: Compare<K, D>> Drop for BinomialPeekMut<'_, K, D, C> {
+    fn drop(&mut self) {
+        if self.modified {
+            self.heap.restore_min();
+        }
+    }
+}
+
+impl<K, D, C: Compare<K, D>> Heap<K, D> for BinomialHeap<K, D, C> {
+    type EntryRef = Handle;
+    type PeekMut<'a>
+        = BinomialPeekMut<'a, K, D, C>
+    where
+        Self: 'a;
+
+    fn insert(&mut self, entry: HeapEntry<K, D>) -> Handle {
+        let index = self.add_node(entry);
+        self.merge_ranks(vec![Some(index)]);
+
+        self.nodes[index].handle
+    }
+
+    fn delete_min(&mut self) -> Option<HeapEntry<K, D>> {
+        if let Some(min_rank) = self.min_rank {
+            let root = self.detach_root(min_rank);
+            let node = self.nodes.remove(root);
+            self.handles.remove(node.handle);
+
+            Some(node.entry)
+        } else {
+            None
+        }
+    }
+
+    fn peek(&self) -> Option<&HeapEntry<K, D>> {
+        self.min_rank
+            .map(|min_rank| &self.nodes[self.ranks[min_rank].unwrap()].entry)
+    }
+
+    fn remove(&mut self, reference: Handle) -> Option<HeapEntry<K, D>> {
+        let mut index = self.handles.get(reference)?;
+        while let Some(parent) = self.nodes[index].parent {
+            self.swap_entries(index, parent);
+            index = parent;
+        }
+
+        let root = self.detach_root(self.nodes[index].childs.len());
+        let node = self.nodes.remove(root);
+        self.handles.remove(node.handle);
+
+        Some(node.entry)
+    }
+
+    fn peek_mut(&mut self) -> Option<BinomialPeekMut<'_, K, D, C>> {
+        if self.min_rank.is_none() {
+            None
+        } else {
+            Some(BinomialPeekMut {
+                heap: self,
+                modified: false,
+            })
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.handles.clear();
+        self.ranks.clear();
+        self.min_rank = None;
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a HeapEntry<K, D>>
+    where
+        K: 'a,
+        D: 'a,
+    {
+        self.nodes.iter().map(|node| &node.entry)
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = HeapEntry<K, D>> {
+        self.handles.clear();
+        self.ranks.clear();
+        self.min_rank = None;
+
+        mem::replace(&mut self.nodes, Arena::new())
+            .into_iter()
+            .map(|node| node.entry)
+    }
+}
+
+impl<K, D, C: Compare<K, D>> DecreaseKeyHeap<K, D> for BinomialHeap<K, D, C> {
+    fn decrease_key(&mut self, reference: Handle, new_key: K) -> Result<(), HeapError> {
+        let index = self.handles.get(reference).ok_or(HeapError::StaleHandle)?;
+
+        let entry = &self.nodes[index].entry;
+        if self
+            .compare
+            .compare(&new_key, &entry.data, &entry.key, &entry.data)
+            == Ordering::Greater
+        {
+            return Err(HeapError::KeyNotDecreased);
+        }
+
+        self.nodes[index].entry.key = new_key;
+        self.sift_up(index);
+        self.update_min_rank();
+
+        Ok(())
+    }
+
+    fn change_key(&mut self, reference: Handle, new_key: K) -> Result<(), HeapError> {
+        let index = self.handles.get(reference).ok_or(HeapError::StaleHandle)?;
+
+        let entry = &self.nodes[index].entry;
+        if self
+            .compare
+            .compare(&new_key, &entry.data, &entry.key, &entry.data)
+            == Ordering::Greater
+        {
+            self.nodes[index].entry.key = new_key;
+            self.sift_down(index);
+        } else {
+            self.nodes[index].entry.key = new_key;
+            self.sift_up(index);
+        }
+
+        self.update_min_rank();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BinomialHeap;
+    use crate::{testing, ByData, MaxOrder};
+
+    #[test]
+    fn matches_model() {
+        testing::check_decrease_key_against_model(|_| BinomialHeap::new());
+    }
+
+    #[test]
+    fn stable_matches_model() {
+        testing::check_decrease_key_against_model(|_| BinomialHeap::new_stable());
+    }
+
+    #[test]
+    fn max_order_matches_model() {
+        testing::check_compare_against_model(MaxOrder, |_| BinomialHeap::with_compare(MaxOrder));
+    }
+
+    #[test]
+    fn by_data_matches_model() {
+        let by_data = ByData(|data: &usize| data % 10);
+        testing::check_compare_against_model(by_data, |_| BinomialHeap::with_compare(by_data));
+    }
+
+    #[test]
+    fn closure_matches_model() {
+        let by_residue = |a: &u64, b: &u64| (a % 8).cmp(&(b % 8));
+        testing::check_compare_against_model(by_residue, |_| {
+            BinomialHeap::with_compare(by_residue)
+        });
+    }
+
+    #[test]
+    fn drain_and_iterators_round_trip() {
+        testing::check_drain_and_iterators(BinomialHeap::new);
+    }
+
+    #[test]
+    fn meld_keeps_handles_of_both_heaps() {
+        testing::check_meld_handles(BinomialHeap::new);
+    }
+
+    #[test]
+    fn stable_keeps_fifo_order() {
+        testing::check_fifo(BinomialHeap::new_stable, BinomialHeap::from_vec_stable);
+    }
+
+    #[test]
+    fn stable_decrease_key_keeps_fifo_order() {
+        testing::check_fifo_decrease_key(BinomialHeap::new_stable);
+    }
+}
@@ -0,0 +1,50 @@
+use std::cmp::Ordering;
+
+use crate::HeapEntry;
+
+/// Decides which of two entries comes out of a heap first: `Less` means the `a` entry does.
+pub trait Compare<K, D> {
+    fn compare(&self, a_key: &K, a_data: &D, b_key: &K, b_data: &D) -> Ordering;
+
+    fn compare_entries(&self, a: &HeapEntry<K, D>, b: &HeapEntry<K, D>) -> Ordering {
+        self.compare(&a.key, &a.data, &b.key, &b.data)
+    }
+
+    fn less(&self, a: &HeapEntry<K, D>, b: &HeapEntry<K, D>) -> bool {
+        self.compare_entries(a, b) == Ordering::Less
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinOrder;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxOrder;
+
+/// Orders entries by a key extracted from their data, ignoring the entry key.
+#[derive(Debug, Clone, Copy)]
+pub struct ByData<F>(pub F);
+
+impl<K: Ord, D> Compare<K, D> for MinOrder {
+    fn compare(&self, a_key: &K, _: &D, b_key: &K, _: &D) -> Ordering {
+        a_key.cmp(b_key)
+    }
+}
+
+impl<K: Ord, D> Compare<K, D> for MaxOrder {
+    fn compare(&self, a_key: &K, _: &D, b_key: &K, _: &D) -> Ordering {
+        b_key.cmp(a_key)
+    }
+}
+
+impl<K, D, T: Ord, F: Fn(&D) -> T> Compare<K, D> for ByData<F> {
+    fn compare(&self, _: &K, a_data: &D, _: &K, b_data: &D) -> Ordering {
+        (self.0)(a_data).cmp(&(self.0)(b_data))
+    }
+}
+
+impl<K, D, F: Fn(&K, &K) -> Ordering> Compare<K, D> for F {
+    fn compare(&self, a_key: &K, _: &D, b_key: &K, _: &D) -> Ordering {
+        self(a_key, b_key)
+    }
+}
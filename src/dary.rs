@@ -0,0 +1,419 @@
+use std::cmp::Ordering;
+use std::ops::{Deref, DerefMut};
+
+use crate::compare::{Compare, MinOrder};
+use crate::handle::{Handle, HandleMap};
+use crate::sequence::Sequence;
+use crate::{DecreaseKeyHeap, Heap, HeapEntry, HeapError, MeldableHeap};
+
+pub type BinaryHeap<K, D, C = MinOrder> = DaryHeap<K, D, 2, C>;
+
+pub struct DaryHeap<K, D, const ARITY: usize, C = MinOrder> {
+    storage: Vec<HeapEntry<K, D>>,
+    handles: Vec<Handle>,
+    positions: HandleMap,
+    seqs: Vec<u64>,
+    sequence: Sequence,
+    compare: C,
+}
+
+impl<K: Ord, D, const ARITY: usize> DaryHeap<K, D, ARITY> {
+    pub fn new() -> Self {
+        DaryHeap::with_compare(MinOrder)
+    }
+
+    pub fn new_stable() -> Self {
+        DaryHeap::with_compare_stable(MinOrder)
+    }
+
+    pub fn from_vec(entries: Vec<HeapEntry<K, D>>) -> Self {
+        DaryHeap::from_vec_with_compare(entries, MinOrder)
+    }
+
+    /// Like `from_vec`, with ties kept in the order of `entries`.
+    pub fn from_vec_stable(entries: Vec<HeapEntry<K, D>>) -> Self {
+        DaryHeap::from_vec_with_compare_stable(entries, MinOrder)
+    }
+}
+
+impl<K, D, const ARITY: usize, C> DaryHeap<K, D, ARITY, C> {
+    pub fn with_compare(compare: C) -> Self {
+        DaryHeap::empty(compare, Sequence::new())
+    }
+
+    pub fn with_compare_stable(compare: C) -> Self {
+        DaryHeap::empty(compare, Sequence::stable())
+    }
+
+    fn empty(compare: C, sequence: Sequence) -> Self {
+        const { assert!(ARITY >= 2, "a d-ary heap needs an arity of at least 2") };
+
+        DaryHeap {
+            storage: Vec::new(),
+            handles: Vec::new(),
+            positions: HandleMap::new(),
+            seqs: Vec::new(),
+            sequence,
+            compare,
+        }
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.storage.swap(a, b);
+        self.handles.swap(a, b);
+        self.seqs.swap(a, b);
+        self.positions.set(self.handles[a], a);
+        self.positions.set(self.handles[b], b);
+    }
+}
+
+impl<K, D, const ARITY: usize, C: Compare<K, D>> DaryHeap<K, D, ARITY, C> {
+    pub fn from_vec_with_compare(entries: Vec<HeapEntry<K, D>>, compare: C) -> Self {
+        DaryHeap::build(entries, compare, Sequence::new())
+    }
+
+    pub fn from_vec_with_compare_stable(entries: Vec<HeapEntry<K, D>>, compare: C) -> Self {
+        DaryHeap::build(entries, compare, Sequence::stable())
+    }
+
+    fn build(entries: Vec<HeapEntry<K, D>>, compare: C, mut sequence: Sequence) -> Self {
+        const { assert!(ARITY >= 2, "a d-ary heap needs an arity of at least 2") };
+
+        let mut positions = HandleMap::new();
+        let handles = (0..entries.len())
+            .map(|position| positions.insert(position))
+            .collect();
+        let seqs = entries.iter().map(|_| sequence.next()).collect();
+
+        let mut heap = DaryHeap {
+            storage: entries,
+            handles,
+            positions,
+            seqs,
+            sequence,
+            compare,
+        };
+        heap.rebuild();
+
+        heap
+    }
+
+    fn order(&self, a: usize, b: usize) -> Ordering {
+        let ordering = self
+            .compare
+            .compare_entries(&self.storage[a], &self.storage[b]);
+        self.sequence.tiebreak(ordering, self.seqs[a], self.seqs[b])
+    }
+
+    fn sift_up(&mut self, mut current_index: usize) {
+        while current_index != 0 {
+            let parent_index = (current_index - 1) / ARITY;
+
+            if self.order(current_index, parent_index).is_lt() {
+                self.swap(current_index, parent_index);
+                current_index = parent_index;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn restore(&mut self, index: usize) {
+        if index != 0 && self.order(index, (index - 1) / ARITY).is_lt() {
+            self.sift_up(index);
+        } else {
+            self.sift_down(index);
+        }
+    }
+
+    fn rebuild(&mut self) {
+        if self.storage.len() > 1 {
+            for index in (0..=(self.storage.len() - 2) / ARITY).rev() {
+                self.sift_down(index);
+            }
+        }
+    }
+
+    fn sift_down(&mut self, mut current_index: usize) {
+        loop {
+            let child_index = ARITY * current_index + 1;
+
+            if child_index < self.storage.len() {
+                let max_child_index = (child_index + ARITY - 1).min(self.storage.len() - 1);
+
+                let max_index = (child_index..=max_child_index)
+                    .min_by(|&a, &b| self.order(a, b))
+                    .unwrap();
+
+                if self.order(max_index, current_index).is_lt() {
+                    self.swap(current_index, max_index);
+                    current_index = max_index;
+                    continue;
+                }
+            }
+
+            break;
+        }
+    }
+}
+
+impl<K: Ord, D, const ARITY: usize> Default for DaryHeap<K, D, ARITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, D, const ARITY: usize, C: Compare<K, D> + Default> FromIterator<HeapEntry<K, D>>
+    for DaryHeap<K, D, ARITY, C>
+{
+    fn from_iter<I: IntoIterator<Item = HeapEntry<K, D>>>(iter: I) -> Self {
+        DaryHeap::from_vec_with_compare(iter.into_iter().collect(), C::default())
+    }
+}
+
+impl<K, D, const ARITY: usize, C> IntoIterator for DaryHeap<K, D, ARITY, C> {
+    type Item = HeapEntry<K, D>;
+    type IntoIter = std::vec::IntoIter<HeapEntry<K, D>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.storage.into_iter()
+    }
+}
+
+pub struct DaryPeekMut<'a, K, D, const ARITY: usize, C: Compare<K, D>> {
+    heap: &'a mut DaryHeap<K, D, ARITY, C>,
+    modified: bool,
+}
+
+impl<K, D, const ARITY: usize, C: Compare<K, D>> Deref for DaryPeekMut<'_, K, D, ARITY, C> {
+    type Target = HeapEntry<K, D>;
+
+    fn deref(&self) -> &HeapEntry<K, D> {
+        &self.heap.storage[0]
+    }
+}
+
+impl<K, D, const ARITY: usize, C: Compare<K, D>> DerefMut for DaryPeekMut<'_, K, D, ARITY, C> {
+    fn deref_mut(&mut self) -> &mut HeapEntry<K, D> {
+        self.modified = true;
+        &mut self.heap.storage[0]
+    }
+}
+
+impl<K, D, const ARITY: usize, C: Compare<K, D>> Drop for DaryPeekMut<'_, K, D, ARITY, C> {
+    fn drop(&mut self) {
+        if self.modified {
+            self.heap.sift_down(0);
+        }
+    }
+}
+
+impl<K, D, const ARITY: usize, C: Compare<K, D>> Heap<K, D> for DaryHeap<K, D, ARITY, C> {
+    type EntryRef = Handle;
+    type PeekMut<'a>
+        = DaryPeekMut<'a, K, D, ARITY, C>
+    where
+        Self: 'a;
+
+    fn insert(&mut self, entry: HeapEntry<K, D>) -> Handle {
+        let handle = self.positions.insert(self.storage.len());
+        self.storage.push(entry);
+        self.handles.push(handle);
+        self.seqs.push(self.sequence.next());
+
+        self.sift_up(self.storage.len() - 1);
+
+        handle
+    }
+
+    fn delete_min(&mut self) -> Option<HeapEntry<K, D>> {
+        if self.storage.is_empty() {
+            return None;
+        }
+
+        let last_index = self.storage.len() - 1;
+        self.swap(0, last_index);
+        let handle = self.handles.pop().unwrap();
+        self.positions.remove(handle);
+        self.seqs.pop();
+        let root = self.storage.pop();
+
+        self.sift_down(0);
+
+        root
+    }
+
+    fn peek(&self) -> Option<&HeapEntry<K, D>> {
+        self.storage.first()
+    }
+
+    fn remove(&mut self, reference: Handle) -> Option<HeapEntry<K, D>> {
+        let index = self.positions.get(reference)?;
+
+        let last_index = self.storage.len() - 1;
+        self.swap(index, last_index);
+        self.handles.pop();
+        self.positions.remove(reference);
+        self.seqs.pop();
+        let entry = self.storage.pop();
+
+        if index < self.storage.len() {
+            self.restore(index);
+        }
+
+        entry
+    }
+
+    fn peek_mut(&mut self) -> Option<DaryPeekMut<'_, K, D, ARITY, C>> {
+        if self.storage.is_empty() {
+            None
+        } else {
+            Some(DaryPeekMut {
+                heap: self,
+                modified: false,
+            })
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.storage.len()
+    }
+
+    fn clear(&mut self) {
+        self.storage.clear();
+        self.handles.clear();
+        self.positions.clear();
+        self.seqs.clear();
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a HeapEntry<K, D>>
+    where
+        K: 'a,
+        D: 'a,
+    {
+        self.storage.iter()
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = HeapEntry<K, D>> {
+        self.handles.clear();
+        self.positions.clear();
+        self.seqs.clear();
+        self.storage.drain(..)
+    }
+}
+
+impl<K, D, const ARITY: usize, C: Compare<K, D>> MeldableHeap<K, D> for DaryHeap<K, D, ARITY, C> {
+    fn meld(&mut self, other: Self) {
+        let offset = self.sequence.append(&other.sequence);
+        self.positions.append(other.positions);
+        let moved = other.storage.into_iter().zip(other.handles).zip(other.seqs);
+        for ((entry, handle), seq) in moved {
+            self.positions.set(handle, self.storage.len());
+            self.storage.push(entry);
+            self.handles.push(handle);
+            self.seqs.push(seq.wrapping_add(offset));
+        }
+
+        self.rebuild();
+    }
+}
+
+impl<K, D, const ARITY: usize, C: Compare<K, D>> DecreaseKeyHeap<K, D>
+    for DaryHeap<K, D, ARITY, C>
+{
+    fn decrease_key(&mut self, reference: Handle, new_key: K) -> Result<(), HeapError> {
+        let current_index = self
+            .positions
+            .get(reference)
+            .ok_or(HeapError::StaleHandle)?;
+
+        let entry = &self.storage[current_index];
+        if self
+            .compare
+            .compare(&new_key, &entry.data, &entry.key, &entry.data)
+            == Ordering::Greater
+        {
+            return Err(HeapError::KeyNotDecreased);
+        }
+
+        self.storage[current_index].key = new_key;
+        self.sift_up(current_index);
+
+        Ok(())
+    }
+
+    fn change_key(&mut self, reference: Handle, new_key: K) -> Result<(), HeapError> {
+        let current_index = self
+            .positions
+            .get(reference)
+            .ok_or(HeapError::StaleHandle)?;
+
+        let entry = &self.storage[current_index];
+        if self
+            .compare
+            .compare(&new_key, &entry.data, &entry.key, &entry.data)
+            == Ordering::Greater
+        {
+            self.storage[current_index].key = new_key;
+            self.sift_down(current_index);
+        } else {
+            self.storage[current_index].key = new_key;
+            self.sift_up(current_index);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BinaryHeap;
+    use crate::{testing, ByData, MaxOrder};
+
+    #[test]
+    fn matches_model() {
+        testing::check_decrease_key_against_model(|_| BinaryHeap::new());
+    }
+
+    #[test]
+    fn stable_matches_model() {
+        testing::check_decrease_key_against_model(|_| BinaryHeap::new_stable());
+    }
+
+    #[test]
+    fn max_order_matches_model() {
+        testing::check_compare_against_model(MaxOrder, |_| BinaryHeap::with_compare(MaxOrder));
+    }
+
+    #[test]
+    fn by_data_matches_model() {
+        let by_data = ByData(|data: &usize| data % 10);
+        testing::check_compare_against_model(by_data, |_| BinaryHeap::with_compare(by_data));
+    }
+
+    #[test]
+    fn closure_matches_model() {
+        let by_residue = |a: &u64, b: &u64| (a % 8).cmp(&(b % 8));
+        testing::check_compare_against_model(by_residue, |_| BinaryHeap::with_compare(by_residue));
+    }
+
+    #[test]
+    fn drain_and_iterators_round_trip() {
+        testing::check_drain_and_iterators(BinaryHeap::new);
+    }
+
+    #[test]
+    fn meld_keeps_handles_of_both_heaps() {
+        testing::check_meld_handles(BinaryHeap::new);
+    }
+
+    #[test]
+    fn stable_keeps_fifo_order() {
+        testing::check_fifo(BinaryHeap::new_stable, BinaryHeap::from_vec_stable);
+    }
+
+    #[test]
+    fn stable_decrease_key_keeps_fifo_order() {
+        testing::check_fifo_decrease_key(BinaryHeap::new_stable);
+    }
+}
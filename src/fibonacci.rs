@@ -0,0 +1,476 @@
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+use crate::arena::Arena;
+use crate::handle::{Handle, HandleMap};
+use crate::sequence::Sequence;
+use crate::{DecreaseKeyHeap, Heap, HeapEntry, HeapError, MeldableHeap};
+
+struct FibonacciNode<K, D> {
+    entry: HeapEntry<K, D>,
+    handle: Handle,
+    seq: u64,
+    parent: Option<usize>,
+    child: Option<usize>,
+    left: usize,
+    right: usize,
+    degree: usize,
+    marked: bool,
+}
+
+pub struct FibonacciHeap<K, D> {
+    nodes: Arena<FibonacciNode<K, D>>,
+    handles: HandleMap,
+    min: Option<usize>,
+    sequence: Sequence,
+}
+
+impl<K: Ord, D> FibonacciHeap<K, D> {
+    pub fn new() -> FibonacciHeap<K, D> {
+        FibonacciHeap::empty(Sequence::new())
+    }
+
+    pub fn new_stable() -> FibonacciHeap<K, D> {
+        FibonacciHeap::empty(Sequence::stable())
+    }
+
+    pub fn from_vec(entries: Vec<HeapEntry<K, D>>) -> FibonacciHeap<K, D> {
+        FibonacciHeap::build(entries, Sequence::new())
+    }
+
+    /// Like `from_vec`, with ties kept in the order of `entries`.
+    pub fn from_vec_stable(entries: Vec<HeapEntry<K, D>>) -> FibonacciHeap<K, D> {
+        FibonacciHeap::build(entries, Sequence::stable())
+    }
+
+    fn empty(sequence: Sequence) -> FibonacciHeap<K, D> {
+        FibonacciHeap {
+            nodes: Arena::new(),
+            handles: HandleMap::new(),
+            min: None,
+            sequence,
+        }
+    }
+
+    fn build(entries: Vec<HeapEntry<K, D>>, sequence: Sequence) -> FibonacciHeap<K, D> {
+        let mut heap = FibonacciHeap::empty(sequence);
+        for entry in entries {
+            heap.insert(entry);
+        }
+
+        heap
+    }
+
+    fn less(&self, a: usize, b: usize) -> bool {
+        let (a, b) = (&self.nodes[a], &self.nodes[b]);
+        let ordering = a.entry.key.cmp(&b.entry.key);
+        self.sequence.tiebreak(ordering, a.seq, b.seq).is_lt()
+    }
+
+    fn add_node(&mut self, entry: HeapEntry<K, D>) -> usize {
+        let handle = self.handles.insert(0);
+        let index = self.nodes.insert(FibonacciNode {
+            entry,
+            handle,
+            seq: self.sequence.next(),
+            parent: None,
+            child: None,
+            left: 0,
+            right: 0,
+            degree: 0,
+            marked: false,
+        });
+        self.nodes[index].left = index;
+        self.nodes[index].right = index;
+        self.handles.set(handle, index);
+
+        index
+    }
+
+    fn splice(&mut self, a: usize, b: usize) {
+        let a_right = self.nodes[a].right;
+        let b_left = self.nodes[b].left;
+
+        self.nodes[a].right = b;
+        self.nodes[b].left = a;
+        self.nodes[b_left].right = a_right;
+        self.nodes[a_right].left = b_left;
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let FibonacciNode { left, right, .. } = self.nodes[index];
+
+        self.nodes[left].right = right;
+        self.nodes[right].left = left;
+        self.nodes[index].left = index;
+        self.nodes[index].right = index;
+    }
+
+    fn siblings(&self, start: usize) -> Vec<usize> {
+        let mut siblings = vec![start];
+        let mut current = self.nodes[start].right;
+        while current != start {
+            siblings.push(current);
+            current = self.nodes[current].right;
+        }
+
+        siblings
+    }
+
+    fn add_root(&mut self, index: usize) {
+        self.nodes[index].parent = None;
+        self.nodes[index].marked = false;
+
+        if let Some(min) = self.min {
+            self.splice(min, index);
+            if self.less(index, min) {
+                self.min = Some(index);
+            }
+        } else {
+            self.min = Some(index);
+        }
+    }
+
+    fn link(&mut self, child: usize, root: usize) {
+        self.nodes[child].parent = Some(root);
+        self.nodes[child].marked = false;
+
+        if let Some(first_child) = self.nodes[root].child {
+            self.splice(first_child, child);
+        } else {
+            self.nodes[root].child = Some(child);
+        }
+
+        self.nodes[root].degree += 1;
+    }
+
+    fn consolidate(&mut self, start: usize) {
+        let mut by_degree: Vec<Option<usize>> = Vec::new();
+        for mut root in self.siblings(start) {
+            self.unlink(root);
+
+            loop {
+                let degree = self.nodes[root].degree;
+                if degree >= by_degree.len() {
+                    by_degree.resize(degree + 1, None);
+                }
+
+                if let Some(mut other) = by_degree[degree].take() {
+                    if self.less(other, root) {
+                        mem::swap(&mut root, &mut other);
+                    }
+
+                    self.link(other, root);
+                } else {
+                    by_degree[degree] = Some(root);
+                    break;
+                }
+            }
+        }
+
+        self.min = None;
+        for root in by_degree.into_iter().flatten() {
+            self.add_root(root);
+        }
+    }
+
+    fn cut(&mut self, index: usize) {
+        let parent = self.nodes[index].parent.unwrap();
+        if self.nodes[parent].child == Some(index) {
+            let right = self.nodes[index].right;
+            self.nodes[parent].child = if right == index { None } else { Some(right) };
+        }
+
+        self.unlink(index);
+        self.nodes[parent].degree -= 1;
+        self.add_root(index);
+    }
+
+    fn cascading_cut(&mut self, mut index: usize) {
+        while let Some(parent) = self.nodes[index].parent {
+            if !self.nodes[index].marked {
+                self.nodes[index].marked = true;
+                break;
+            }
+
+            self.cut(index);
+            index = parent;
+        }
+    }
+
+    fn extract(&mut self, index: usize) {
+        if let Some(parent) = self.nodes[index].parent {
+            self.cut(index);
+            self.cascading_cut(parent);
+        }
+
+        if let Some(child) = self.nodes[index].child.take() {
+            for sibling in self.siblings(child) {
+                self.nodes[sibling].parent = None;
+                self.nodes[sibling].marked = false;
+            }
+
+            self.splice(index, child);
+            self.nodes[index].degree = 0;
+        }
+
+        let next = self.nodes[index].right;
+        self.unlink(index);
+
+        if self.min == Some(index) {
+            if next == index {
+                self.min = None;
+            } else {
+                self.consolidate(next);
+            }
+        }
+    }
+}
+
+impl<K: Ord, D> Default for FibonacciHeap<K, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, D> FromIterator<HeapEntry<K, D>> for FibonacciHeap<K, D> {
+    fn from_iter<I: IntoIterator<Item = HeapEntry<K, D>>>(iter: I) -> Self {
+        FibonacciHeap::from_vec(iter.into_iter().collect())
+    }
+}
+
+impl<K, D> IntoIterator for FibonacciHeap<K, D> {
+    type Item = HeapEntry<K, D>;
+    type IntoIter = IntoIter<K, D>;
+
+    fn into_iter(self) -> IntoIter<K, D> {
+        IntoIter {
+            nodes: self.nodes.into_iter(),
+        }
+    }
+}
+
+pub struct IntoIter<K, D> {
+    nodes: <Arena<FibonacciNode<K, D>> as IntoIterator>::IntoIter,
+}
+
+impl<K, D> Iterator for IntoIter<K, D> {
+    type Item = HeapEntry<K, D>;
+
+    fn next(&mut self) -> Option<HeapEntry<K, D>> {
+        self.nodes.next().map(|node| node.entry)
+    }
+}
+
+pub struct FibonacciPeekMut<'a, K: Ord, D> {
+    heap: &'a mut FibonacciHeap<K, D>,
+    modified: bool,
+}
+
+impl<K: Ord, D> Deref for FibonacciPeekMut<'_, K, D> {
+    type Target = HeapEntry<K, D>;
+
+    fn deref(&self) -> &HeapEntry<K, D> {
+        &self.heap.nodes[self.heap.min.unwrap()].entry
+    }
+}
+
+impl<K: Ord, D> DerefMut for FibonacciPeekMut<'_, K, D> {
+    fn deref_mut(&mut self) -> &mut HeapEntry<K, D> {
+        self.modified = true;
+        let min = self.heap.min.unwrap();
+        &mut self.heap.nodes[min].entry
+    }
+}
+
+impl<K: Ord, D> Drop for FibonacciPeekMut<'_, K, D> {
+    fn drop(&mut self) {
+        if self.modified {
+            let min = self.heap.min.unwrap();
+            self.heap.extract(min);
+            self.heap.add_root(min);
+        }
+    }
+}
+
+impl<K: Ord, D> Heap<K, D> for FibonacciHeap<K, D> {
+    type EntryRef = Handle;
+    type PeekMut<'a>
+        = FibonacciPeekMut<'a, K, D>
+    where
+        Self: 'a;
+
+    fn insert(&mut self, entry: HeapEntry<K, D>) -> Handle {
+        let index = self.add_node(entry);
+        self.add_root(index);
+
+        self.nodes[index].handle
+    }
+
+    fn delete_min(&mut self) -> Option<HeapEntry<K, D>> {
+        let min = self.min?;
+        self.remove(self.nodes[min].handle)
+    }
+
+    fn peek(&self) -> Option<&HeapEntry<K, D>> {
+        self.min.map(|min| &self.nodes[min].entry)
+    }
+
+    fn remove(&mut self, reference: Handle) -> Option<HeapEntry<K, D>> {
+        let index = self.handles.remove(reference)?;
+        self.extract(index);
+
+        Some(self.nodes.remove(index).entry)
+    }
+
+    fn peek_mut(&mut self) -> Option<FibonacciPeekMut<'_, K, D>> {
+        if self.min.is_none() {
+            None
+        } else {
+            Some(FibonacciPeekMut {
+                heap: self,
+                modified: false,
+            })
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.handles.clear();
+        self.min = None;
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a HeapEntry<K, D>>
+    where
+        K: 'a,
+        D: 'a,
+    {
+        self.nodes.iter().map(|node| &node.entry)
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = HeapEntry<K, D>> {
+        self.handles.clear();
+        self.min = None;
+
+        mem::replace(&mut self.nodes, Arena::new())
+            .into_iter()
+            .map(|node| node.entry)
+    }
+}
+
+impl<K: Ord, D> MeldableHeap<K, D> for FibonacciHeap<K, D> {
+    fn meld(&mut self, mut other: Self) {
+        // Only the nodes of the heap with the smaller arena are moved.
+        let moves_self = self.nodes.slots() < other.nodes.slots();
+        let offset = if moves_self {
+            mem::swap(&mut self.nodes, &mut other.nodes);
+            mem::swap(&mut self.handles, &mut other.handles);
+            mem::swap(&mut self.min, &mut other.min);
+            self.sequence.append_to(&other.sequence)
+        } else {
+            self.sequence.append(&other.sequence)
+        };
+        let mapping = self.nodes.append(other.nodes);
+        self.handles.append(other.handles);
+        for &index in mapping.iter().flatten() {
+            let node = &mut self.nodes[index];
+            node.seq = node.seq.wrapping_add(offset);
+            node.parent = node.parent.map(|parent| mapping[parent].unwrap());
+            node.child = node.child.map(|child| mapping[child].unwrap());
+            node.left = mapping[node.left].unwrap();
+            node.right = mapping[node.right].unwrap();
+
+            self.handles.set(node.handle, index);
+        }
+
+        if let Some(other_min) = other.min.map(|min| mapping[min].unwrap()) {
+            if let Some(min) = self.min {
+                self.splice(min, other_min);
+                if self.less(other_min, min) {
+                    self.min = Some(other_min);
+                }
+            } else {
+                self.min = Some(other_min);
+            }
+        }
+    }
+}
+
+impl<K: Ord, D> DecreaseKeyHeap<K, D> for FibonacciHeap<K, D> {
+    fn decrease_key(&mut self, reference: Handle, new_key: K) -> Result<(), HeapError> {
+        let index = self.handles.get(reference).ok_or(HeapError::StaleHandle)?;
+
+        if new_key > self.nodes[index].entry.key {
+            return Err(HeapError::KeyNotDecreased);
+        }
+
+        self.nodes[index].entry.key = new_key;
+        if let Some(parent) = self.nodes[index].parent {
+            if self.less(index, parent) {
+                self.cut(index);
+                self.cascading_cut(parent);
+            }
+        } else {
+            let min = self.min.unwrap();
+            if self.less(index, min) {
+                self.min = Some(index);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn change_key(&mut self, reference: Handle, new_key: K) -> Result<(), HeapError> {
+        let index = self.handles.get(reference).ok_or(HeapError::StaleHandle)?;
+
+        if new_key <= self.nodes[index].entry.key {
+            return self.decrease_key(reference, new_key);
+        }
+
+        self.extract(index);
+        self.nodes[index].entry.key = new_key;
+        self.add_root(index);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FibonacciHeap;
+    use crate::testing;
+
+    #[test]
+    fn matches_model() {
+        testing::check_decrease_key_against_model(|_| FibonacciHeap::new());
+    }
+
+    #[test]
+    fn stable_matches_model() {
+        testing::check_decrease_key_against_model(|_| FibonacciHeap::new_stable());
+    }
+
+    #[test]
+    fn drain_and_iterators_round_trip() {
+        testing::check_drain_and_iterators(FibonacciHeap::new);
+    }
+
+    #[test]
+    fn meld_keeps_handles_of_both_heaps() {
+        testing::check_meld_handles(FibonacciHeap::new);
+    }
+
+    #[test]
+    fn stable_keeps_fifo_order() {
+        testing::check_fifo(FibonacciHeap::new_stable, FibonacciHeap::from_vec_stable);
+    }
+
+    #[test]
+    fn stable_decrease_key_keeps_fifo_order() {
+        testing::check_fifo_decrease_key(FibonacciHeap::new_stable);
+    }
+}
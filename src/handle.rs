@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_MAP: AtomicU64 = AtomicU64::new(0);
+
+/// Reference to an entry of the heap that handed it out. Besides its slot, a handle names the
+/// map that issued it, so a handle from another heap is reported as stale instead of reaching an
+/// unrelated entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    map: u64,
+    index: usize,
+    generation: u32,
+}
+
+#[derive(Debug)]
+struct HandleSlot {
+    generation: u32,
+    position: Option<usize>,
+}
+
+#[derive(Debug)]
+pub(crate) struct HandleMap {
+    id: u64,
+    slots: Vec<HandleSlot>,
+    free: Vec<usize>,
+    /// Maps absorbed by `append`, with the offset their slots were moved to.
+    appended: HashMap<u64, usize>,
+}
+
+impl HandleMap {
+    pub(crate) fn new() -> HandleMap {
+        HandleMap {
+            id: NEXT_MAP.fetch_add(1, Ordering::Relaxed),
+            slots: Vec::new(),
+            free: Vec::new(),
+            appended: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, position: usize) -> Handle {
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index];
+            slot.position = Some(position);
+
+            Handle {
+                map: self.id,
+                index,
+                generation: slot.generation,
+            }
+        } else {
+            self.slots.push(HandleSlot {
+                generation: 0,
+                position: Some(position),
+            });
+
+            Handle {
+                map: self.id,
+                index: self.slots.len() - 1,
+                generation: 0,
+            }
+        }
+    }
+
+    fn slot(&self, handle: Handle) -> Option<usize> {
+        let offset = if handle.map == self.id {
+            0
+        } else {
+            *self.appended.get(&handle.map)?
+        };
+
+        let index = offset + handle.index;
+        let slot = self.slots.get(index)?;
+        (slot.generation == handle.generation && slot.position.is_some()).then_some(index)
+    }
+
+    #[cfg(any(
+        feature = "binomial",
+        feature = "dary",
+        feature = "fibonacci",
+        feature = "pairing",
+        feature = "randomized"
+    ))]
+    pub(crate) fn get(&self, handle: Handle) -> Option<usize> {
+        self.slot(handle)
+            .and_then(|index| self.slots[index].position)
+    }
+
+    pub(crate) fn set(&mut self, handle: Handle, position: usize) {
+        let index = self.slot(handle).unwrap();
+        self.slots[index].position = Some(position);
+    }
+
+    pub(crate) fn remove(&mut self, handle: Handle) -> Option<usize> {
+        let index = self.slot(handle)?;
+
+        let slot = &mut self.slots[index];
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(index);
+
+        slot.position.take()
+    }
+
+    /// Takes over the slots of `other`, so the handles it issued keep working here. Their
+    /// positions still refer to `other`'s storage and must be updated with `set`.
+    pub(crate) fn append(&mut self, other: HandleMap) {
+        let offset = self.slots.len();
+
+        self.appended.insert(other.id, offset);
+        for (id, other_offset) in other.appended {
+            self.appended.insert(id, offset + other_offset);
+        }
+
+        self.free
+            .extend(other.free.into_iter().map(|index| offset + index));
+        self.slots.extend(other.slots);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.position.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(index);
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+#[cfg(any(
+    feature = "binomial",
+    feature = "fibonacci",
+    feature = "leftist",
+    feature = "pairing",
+    feature = "randomized",
+    feature = "skew"
+))]
+mod arena;
+pub mod compare;
+#[cfg(any(
+    feature = "binomial",
+    feature = "dary",
+    feature = "fibonacci",
+    feature = "leftist",
+    feature = "pairing",
+    feature = "randomized",
+    feature = "skew"
+))]
+mod handle;
+#[cfg(any(
+    feature = "binomial",
+    feature = "dary",
+    feature = "fibonacci",
+    feature = "leftist",
+    feature = "pairing",
+    feature = "randomized",
+    feature = "skew"
+))]
+mod sequence;
+
+#[cfg(feature = "binomial")]
+pub mod binomial;
+#[cfg(feature = "dary")]
+pub mod dary;
+#[cfg(feature = "fibonacci")]
+pub mod fibonacci;
+#[cfg(feature = "leftist")]
+pub mod leftist;
+#[cfg(feature = "pairing")]
+pub mod pairing;
+#[cfg(feature = "randomized")]
+pub mod randomized;
+#[cfg(feature = "skew")]
+pub mod skew;
+#[cfg(feature = "workload")]
+pub mod workload;
+
+#[cfg(all(
+    test,
+    any(
+        feature = "binomial",
+        feature = "dary",
+        feature = "fibonacci",
+        feature = "leftist",
+        feature = "pairing",
+        feature = "randomized",
+        feature = "skew"
+    )
+))]
+mod testing;
+
+use std::error::Error;
+use std::fmt;
+use std::iter;
+use std::ops::DerefMut;
+
+pub use compare::{ByData, Compare, MaxOrder, MinOrder};
+#[cfg(any(
+    feature = "binomial",
+    feature = "dary",
+    feature = "fibonacci",
+    feature = "leftist",
+    feature = "pairing",
+    feature = "randomized",
+    feature = "skew"
+))]
+pub use handle::Handle;
+
+#[derive(Debug)]
+pub struct HeapEntry<K, D> {
+    pub key: K,
+    pub data: D,
+}
+
+pub trait Heap<K, D> {
+    type EntryRef;
+    type PeekMut<'a>: DerefMut<Target = HeapEntry<K, D>>
+    where
+        Self: 'a;
+
+    fn insert(&mut self, entry: HeapEntry<K, D>) -> Self::EntryRef;
+    fn delete_min(&mut self) -> Option<HeapEntry<K, D>>;
+    fn peek(&self) -> Option<&HeapEntry<K, D>>;
+
+    /// Removes the referenced entry, or returns `None` if the reference is stale.
+    fn remove(&mut self, reference: Self::EntryRef) -> Option<HeapEntry<K, D>>;
+
+    fn len(&self) -> usize;
+    fn clear(&mut self);
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gives mutable access to the minimum entry. The heap invariant is restored when the
+    /// returned guard is dropped.
+    fn peek_mut(&mut self) -> Option<Self::PeekMut<'_>>;
+
+    /// Visits every entry without removing it, in no particular order.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a HeapEntry<K, D>>
+    where
+        K: 'a,
+        D: 'a;
+
+    /// Removes every entry from the heap, yielding them in no particular order.
+    fn drain(&mut self) -> impl Iterator<Item = HeapEntry<K, D>>;
+
+    fn into_sorted_iter(mut self) -> impl Iterator<Item = HeapEntry<K, D>>
+    where
+        Self: Sized,
+    {
+        iter::from_fn(move || self.delete_min())
+    }
+}
+
+/// Heaps that can absorb every entry of another heap of the same type. References handed out by
+/// either heap keep referring to the same entries in `self` after the meld, and references from
+/// any other heap are reported as stale.
+///
+/// The node-based heaps move the nodes of whichever heap has the smaller arena into the other
+/// one, so a meld costs O(min(n, m)) on top of linking the two structures: O(1) for the Fibonacci
+/// and pairing heaps, O(log(n + m)) for the rest. `DaryHeap` appends `other` and rebuilds in
+/// O(n + m).
+pub trait MeldableHeap<K, D>: Heap<K, D> {
+    fn meld(&mut self, other: Self);
+}
+
+pub trait DecreaseKeyHeap<K, D>: Heap<K, D> {
+    /// Moves the key of the referenced entry towards the top of the heap. Fails with
+    /// `KeyNotDecreased`, leaving the heap untouched, if `new_key` orders after the current key.
+    fn decrease_key(&mut self, reference: Self::EntryRef, new_key: K) -> Result<(), HeapError>;
+
+    /// Replaces the key of the referenced entry, moving it up or down as needed.
+    fn change_key(&mut self, reference: Self::EntryRef, new_key: K) -> Result<(), HeapError>;
+}
+
+/// Errors from the handle-based calls. There is no `Empty` variant: the calls that can meet an
+/// empty heap (`delete_min`, `peek`, `peek_mut`) return `Option`, and a handle into an empty heap
+/// is reported as `StaleHandle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapError {
+    StaleHandle,
+    KeyNotDecreased,
+}
+
+impl fmt::Display for HeapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeapError::StaleHandle => write!(f, "handle does not refer to an entry in the heap"),
+            HeapError::KeyNotDecreased => write!(f, "new key orders after the current key"),
+        }
+    }
+}
+
+impl Error for HeapError {}
@@ -0,0 +1,414 @@
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+use crate::arena::Arena;
+use crate::handle::{Handle, HandleMap};
+use crate::sequence::Sequence;
+use crate::{DecreaseKeyHeap, Heap, HeapEntry, HeapError, MeldableHeap};
+
+struct PairingNode<K, D> {
+    entry: HeapEntry<K, D>,
+    handle: Handle,
+    seq: u64,
+    child: Option<usize>,
+    next: Option<usize>,
+    prev: Option<usize>,
+}
+
+pub struct PairingHeap<K, D> {
+    nodes: Arena<PairingNode<K, D>>,
+    handles: HandleMap,
+    root: Option<usize>,
+    sequence: Sequence,
+}
+
+impl<K: Ord, D> PairingHeap<K, D> {
+    pub fn new() -> PairingHeap<K, D> {
+        PairingHeap::empty(Sequence::new())
+    }
+
+    pub fn new_stable() -> PairingHeap<K, D> {
+        PairingHeap::empty(Sequence::stable())
+    }
+
+    pub fn from_vec(entries: Vec<HeapEntry<K, D>>) -> PairingHeap<K, D> {
+        PairingHeap::build(entries, Sequence::new())
+    }
+
+    /// Like `from_vec`, with ties kept in the order of `entries`.
+    pub fn from_vec_stable(entries: Vec<HeapEntry<K, D>>) -> PairingHeap<K, D> {
+        PairingHeap::build(entries, Sequence::stable())
+    }
+
+    fn empty(sequence: Sequence) -> PairingHeap<K, D> {
+        PairingHeap {
+            nodes: Arena::new(),
+            handles: HandleMap::new(),
+            root: None,
+            sequence,
+        }
+    }
+
+    fn build(entries: Vec<HeapEntry<K, D>>, sequence: Sequence) -> PairingHeap<K, D> {
+        let mut heap = PairingHeap::empty(sequence);
+        let mut first = None;
+        let mut last = None;
+        for entry in entries {
+            let index = heap.add_node(entry);
+            heap.nodes[index].prev = last;
+            if let Some(last) = last {
+                heap.nodes[last].next = Some(index);
+            } else {
+                first = Some(index);
+            }
+
+            last = Some(index);
+        }
+
+        heap.root = heap.merge_pairs(first);
+
+        heap
+    }
+
+    fn less(&self, a: usize, b: usize) -> bool {
+        let (a, b) = (&self.nodes[a], &self.nodes[b]);
+        let ordering = a.entry.key.cmp(&b.entry.key);
+        self.sequence.tiebreak(ordering, a.seq, b.seq).is_lt()
+    }
+
+    fn add_node(&mut self, entry: HeapEntry<K, D>) -> usize {
+        let handle = self.handles.insert(0);
+        let index = self.nodes.insert(PairingNode {
+            entry,
+            handle,
+            seq: self.sequence.next(),
+            child: None,
+            next: None,
+            prev: None,
+        });
+        self.handles.set(handle, index);
+
+        index
+    }
+
+    fn link(&mut self, a: usize, b: usize) -> usize {
+        let (root, child) = if self.less(b, a) { (b, a) } else { (a, b) };
+
+        let first_child = self.nodes[root].child;
+        if let Some(first_child) = first_child {
+            self.nodes[first_child].prev = Some(child);
+        }
+
+        self.nodes[child].next = first_child;
+        self.nodes[child].prev = Some(root);
+        self.nodes[root].child = Some(child);
+
+        root
+    }
+
+    fn meld_roots(&mut self, this: Option<usize>, other: Option<usize>) -> Option<usize> {
+        match (this, other) {
+            (Some(this), Some(other)) => Some(self.link(this, other)),
+            (root, None) | (None, root) => root,
+        }
+    }
+
+    fn detach(&mut self, index: usize) {
+        let PairingNode { prev, next, .. } = self.nodes[index];
+
+        if let Some(prev) = prev {
+            if self.nodes[prev].child == Some(index) {
+                self.nodes[prev].child = next;
+            } else {
+                self.nodes[prev].next = next;
+            }
+        }
+
+        if let Some(next) = next {
+            self.nodes[next].prev = prev;
+        }
+
+        self.nodes[index].prev = None;
+        self.nodes[index].next = None;
+    }
+
+    fn merge_pairs(&mut self, first: Option<usize>) -> Option<usize> {
+        let mut pairs = Vec::new();
+        let mut current = first;
+        while let Some(a) = current {
+            let b = self.nodes[a].next;
+            current = b.and_then(|b| self.nodes[b].next);
+
+            self.nodes[a].prev = None;
+            self.nodes[a].next = None;
+            if let Some(b) = b {
+                self.nodes[b].prev = None;
+                self.nodes[b].next = None;
+                pairs.push(self.link(a, b));
+            } else {
+                pairs.push(a);
+            }
+        }
+
+        let mut root = pairs.pop();
+        while let Some(tree) = pairs.pop() {
+            root = self.meld_roots(Some(tree), root);
+        }
+
+        root
+    }
+
+    fn restore_root(&mut self) {
+        if let Some(root) = self.root {
+            let childs = self.nodes[root].child.take();
+            let subtree = self.merge_pairs(childs);
+            self.root = self.meld_roots(subtree, Some(root));
+        }
+    }
+}
+
+impl<K: Ord, D> Default for PairingHeap<K, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, D> FromIterator<HeapEntry<K, D>> for PairingHeap<K, D> {
+    fn from_iter<I: IntoIterator<Item = HeapEntry<K, D>>>(iter: I) -> Self {
+        PairingHeap::from_vec(iter.into_iter().collect())
+    }
+}
+
+impl<K, D> IntoIterator for PairingHeap<K, D> {
+    type Item = HeapEntry<K, D>;
+    type IntoIter = IntoIter<K, D>;
+
+    fn into_iter(self) -> IntoIter<K, D> {
+        IntoIter {
+            nodes: self.nodes.into_iter(),
+        }
+    }
+}
+
+pub struct IntoIter<K, D> {
+    nodes: <Arena<PairingNode<K, D>> as IntoIterator>::IntoIter,
+}
+
+impl<K, D> Iterator for IntoIter<K, D> {
+    type Item = HeapEntry<K, D>;
+
+    fn next(&mut self) -> Option<HeapEntry<K, D>> {
+        self.nodes.next().map(|node| node.entry)
+    }
+}
+
+pub struct PairingPeekMut<'a, K: Ord, D> {
+    heap: &'a mut PairingHeap<K, D>,
+    modified: bool,
+}
+
+impl<K: Ord, D> Deref for PairingPeekMut<'_, K, D> {
+    type Target = HeapEntry<K, D>;
+
+    fn deref(&self) -> &HeapEntry<K, D> {
+        &self.heap.nodes[self.heap.root.unwrap()].entry
+    }
+}
+
+impl<K: Ord, D> DerefMut for PairingPeekMut<'_, K, D> {
+    fn deref_mut(&mut self) -> &mut HeapEntry<K, D> {
+        self.modified = true;
+        let root = self.heap.root.unwrap();
+        &mut self.heap.nodes[root].entry
+    }
+}
+
+impl<K: Ord, D> Drop for PairingPeekMut<'_, K, D> {
+    fn drop(&mut self) {
+        if self.modified {
+            self.heap.restore_root();
+        }
+    }
+}
+
+impl<K: Ord, D> Heap<K, D> for PairingHeap<K, D> {
+    type EntryRef = Handle;
+    type PeekMut<'a>
+        = PairingPeekMut<'a, K, D>
+    where
+        Self: 'a;
+
+    fn insert(&mut self, entry: HeapEntry<K, D>) -> Handle {
+        let index = self.add_node(entry);
+        self.root = self.meld_roots(self.root, Some(index));
+
+        self.nodes[index].handle
+    }
+
+    fn delete_min(&mut self) -> Option<HeapEntry<K, D>> {
+        let root = self.root?;
+        self.remove(self.nodes[root].handle)
+    }
+
+    fn peek(&self) -> Option<&HeapEntry<K, D>> {
+        self.root.map(|root| &self.nodes[root].entry)
+    }
+
+    fn remove(&mut self, reference: Handle) -> Option<HeapEntry<K, D>> {
+        let index = self.handles.remove(reference)?;
+        let childs = self.nodes[index].child.take();
+        let subtree = self.merge_pairs(childs);
+
+        if self.root == Some(index) {
+            self.root = subtree;
+        } else {
+            self.detach(index);
+            self.root = self.meld_roots(self.root, subtree);
+        }
+
+        Some(self.nodes.remove(index).entry)
+    }
+
+    fn peek_mut(&mut self) -> Option<PairingPeekMut<'_, K, D>> {
+        if self.root.is_none() {
+            None
+        } else {
+            Some(PairingPeekMut {
+                heap: self,
+                modified: false,
+            })
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.handles.clear();
+        self.root = None;
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a HeapEntry<K, D>>
+    where
+        K: 'a,
+        D: 'a,
+    {
+        self.nodes.iter().map(|node| &node.entry)
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = HeapEntry<K, D>> {
+        self.handles.clear();
+        self.root = None;
+
+        mem::replace(&mut self.nodes, Arena::new())
+            .into_iter()
+            .map(|node| node.entry)
+    }
+}
+
+impl<K: Ord, D> MeldableHeap<K, D> for PairingHeap<K, D> {
+    fn meld(&mut self, mut other: Self) {
+        // Only the nodes of the heap with the smaller arena are moved.
+        let moves_self = self.nodes.slots() < other.nodes.slots();
+        let offset = if moves_self {
+            mem::swap(&mut self.nodes, &mut other.nodes);
+            mem::swap(&mut self.handles, &mut other.handles);
+            mem::swap(&mut self.root, &mut other.root);
+            self.sequence.append_to(&other.sequence)
+        } else {
+            self.sequence.append(&other.sequence)
+        };
+        let mapping = self.nodes.append(other.nodes);
+        self.handles.append(other.handles);
+        for &index in mapping.iter().flatten() {
+            let node = &mut self.nodes[index];
+            node.seq = node.seq.wrapping_add(offset);
+            node.child = node.child.map(|child| mapping[child].unwrap());
+            node.next = node.next.map(|next| mapping[next].unwrap());
+            node.prev = node.prev.map(|prev| mapping[prev].unwrap());
+
+            self.handles.set(node.handle, index);
+        }
+
+        let other_root = other.root.map(|root| mapping[root].unwrap());
+        self.root = self.meld_roots(self.root, other_root);
+    }
+}
+
+impl<K: Ord, D> DecreaseKeyHeap<K, D> for PairingHeap<K, D> {
+    fn decrease_key(&mut self, reference: Handle, new_key: K) -> Result<(), HeapError> {
+        let index = self.handles.get(reference).ok_or(HeapError::StaleHandle)?;
+
+        if new_key > self.nodes[index].entry.key {
+            return Err(HeapError::KeyNotDecreased);
+        }
+
+        self.nodes[index].entry.key = new_key;
+        if self.root != Some(index) {
+            self.detach(index);
+            self.root = self.meld_roots(self.root, Some(index));
+        }
+
+        Ok(())
+    }
+
+    fn change_key(&mut self, reference: Handle, new_key: K) -> Result<(), HeapError> {
+        let index = self.handles.get(reference).ok_or(HeapError::StaleHandle)?;
+
+        if new_key <= self.nodes[index].entry.key {
+            return self.decrease_key(reference, new_key);
+        }
+
+        self.nodes[index].entry.key = new_key;
+        if self.root == Some(index) {
+            self.restore_root();
+        } else {
+            let childs = self.nodes[index].child.take();
+            let subtree = self.merge_pairs(childs);
+
+            self.detach(index);
+            let root = self.meld_roots(self.root, subtree);
+            self.root = self.meld_roots(root, Some(index));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PairingHeap;
+    use crate::testing;
+
+    #[test]
+    fn matches_model() {
+        testing::check_decrease_key_against_model(|_| PairingHeap::new());
+    }
+
+    #[test]
+    fn stable_matches_model() {
+        testing::check_decrease_key_against_model(|_| PairingHeap::new_stable());
+    }
+
+    #[test]
+    fn drain_and_iterators_round_trip() {
+        testing::check_drain_and_iterators(PairingHeap::new);
+    }
+
+    #[test]
+    fn meld_keeps_handles_of_both_heaps() {
+        testing::check_meld_handles(PairingHeap::new);
+    }
+
+    #[test]
+    fn stable_keeps_fifo_order() {
+        testing::check_fifo(PairingHeap::new_stable, PairingHeap::from_vec_stable);
+    }
+
+    #[test]
+    fn stable_decrease_key_keeps_fifo_order() {
+        testing::check_fifo_decrease_key(PairingHeap::new_stable);
+    }
+}
@@ -0,0 +1,616 @@
+use std::cmp::Ordering;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+use crate::arena::Arena;
+use crate::compare::{Compare, MinOrder};
+use crate::handle::{Handle, HandleMap};
+use crate::sequence::Sequence;
+use crate::{DecreaseKeyHeap, Heap, HeapEntry, HeapError, MeldableHeap};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Unless built with `with_rng`, the heap draws from a `StdRng` seeded from system entropy, which
+/// keeps it `Send`.
+pub struct RandomizedMeldableHeap<K, D, R = StdRng, C = MinOrder> {
+    nodes: Arena<Node<K, D>>,
+    handles: HandleMap,
+    root: Option<usize>,
+    rng: R,
+    sequence: Sequence,
+    compare: C,
+}
+
+struct Node<K, D> {
+    value: HeapEntry<K, D>,
+    handle: Handle,
+    seq: u64,
+    parent: Option<usize>,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+impl<K: Ord, D> RandomizedMeldableHeap<K, D> {
+    pub fn new() -> Self {
+        RandomizedMeldableHeap::with_rng(StdRng::from_entropy())
+    }
+
+    pub fn new_stable() -> Self {
+        RandomizedMeldableHeap::with_rng_and_compare_stable(StdRng::from_entropy(), MinOrder)
+    }
+
+    pub fn from_vec(entries: Vec<HeapEntry<K, D>>) -> Self {
+        RandomizedMeldableHeap::from_vec_with_rng(entries, StdRng::from_entropy())
+    }
+
+    /// Like `from_vec`, with ties kept in the order of `entries`.
+    pub fn from_vec_stable(entries: Vec<HeapEntry<K, D>>) -> Self {
+        RandomizedMeldableHeap::from_vec_with_rng_and_compare_stable(
+            entries,
+            StdRng::from_entropy(),
+            MinOrder,
+        )
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        RandomizedMeldableHeap::with_rng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<K: Ord, D, R: Rng> RandomizedMeldableHeap<K, D, R> {
+    pub fn with_rng(rng: R) -> Self {
+        RandomizedMeldableHeap::with_rng_and_compare(rng, MinOrder)
+    }
+
+    pub fn from_vec_with_rng(entries: Vec<HeapEntry<K, D>>, rng: R) -> Self {
+        RandomizedMeldableHeap::from_vec_with_rng_and_compare(entries, rng, MinOrder)
+    }
+}
+
+impl<K, D, C: Compare<K, D>> RandomizedMeldableHeap<K, D, StdRng, C> {
+    pub fn with_compare(compare: C) -> Self {
+        RandomizedMeldableHeap::with_rng_and_compare(StdRng::from_entropy(), compare)
+    }
+
+    pub fn from_vec_with_compare(entries: Vec<HeapEntry<K, D>>, compare: C) -> Self {
+        RandomizedMeldableHeap::from_vec_with_rng_and_compare(
+            entries,
+            StdRng::from_entropy(),
+            compare,
+        )
+    }
+}
+
+impl<K, D, R: Rng, C: Compare<K, D>> RandomizedMeldableHeap<K, D, R, C> {
+    pub fn with_rng_and_compare(rng: R, compare: C) -> Self {
+        RandomizedMeldableHeap::empty(rng, compare, Sequence::new())
+    }
+
+    pub fn with_rng_and_compare_stable(rng: R, compare: C) -> Self {
+        RandomizedMeldableHeap::empty(rng, compare, Sequence::stable())
+    }
+
+    fn empty(rng: R, compare: C, sequence: Sequence) -> Self {
+        RandomizedMeldableHeap {
+            nodes: Arena::new(),
+            handles: HandleMap::new(),
+            root: None,
+            rng,
+            sequence,
+            compare,
+        }
+    }
+
+    pub fn from_vec_with_rng_and_compare(
+        entries: Vec<HeapEntry<K, D>>,
+        rng: R,
+        compare: C,
+    ) -> Self {
+        RandomizedMeldableHeap::build(entries, rng, compare, Sequence::new())
+    }
+
+    pub fn from_vec_with_rng_and_compare_stable(
+        entries: Vec<HeapEntry<K, D>>,
+        rng: R,
+        compare: C,
+    ) -> Self {
+        RandomizedMeldableHeap::build(entries, rng, compare, Sequence::stable())
+    }
+
+    fn build(entries: Vec<HeapEntry<K, D>>, rng: R, compare: C, mut sequence: Sequence) -> Self {
+        // Stamp the entries in input order before heapifying moves them around.
+        let mut entries = entries
+            .into_iter()
+            .map(|entry| (entry, sequence.next()))
+            .collect::<Vec<_>>();
+        heapify(&mut entries, |(a, a_seq), (b, b_seq)| {
+            let ordering = compare.compare_entries(a, b);
+            sequence.tiebreak(ordering, *a_seq, *b_seq).is_lt()
+        });
+
+        let mut heap = RandomizedMeldableHeap::empty(rng, compare, sequence);
+        for (value, seq) in entries {
+            heap.add_node(value, seq);
+        }
+
+        for index in 1..heap.nodes.len() {
+            let parent = (index - 1) / 2;
+            heap.nodes[index].parent = Some(parent);
+
+            if index % 2 == 1 {
+                heap.nodes[parent].left = Some(index);
+            } else {
+                heap.nodes[parent].right = Some(index);
+            }
+        }
+
+        if heap.nodes.len() != 0 {
+            heap.root = Some(0);
+        }
+
+        heap
+    }
+
+    fn add_node(&mut self, value: HeapEntry<K, D>, seq: u64) -> usize {
+        let handle = self.handles.insert(0);
+        let index = self.nodes.insert(Node {
+            value,
+            handle,
+            seq,
+            parent: None,
+            left: None,
+            right: None,
+        });
+        self.handles.set(handle, index);
+
+        index
+    }
+
+    fn less(&self, a: usize, b: usize) -> bool {
+        let (a, b) = (&self.nodes[a], &self.nodes[b]);
+        let ordering = self.compare.compare_entries(&a.value, &b.value);
+        self.sequence.tiebreak(ordering, a.seq, b.seq).is_lt()
+    }
+
+    fn meld_nodes(
+        &mut self,
+        this: Option<usize>,
+        other: Option<usize>,
+        parent: Option<usize>,
+    ) -> Option<usize> {
+        let (mut current, mut other) = match (this, other) {
+            (Some(this), Some(other)) if self.less(other, this) => (other, this),
+            (Some(this), Some(other)) => (this, other),
+            (Some(root), None) | (None, Some(root)) => {
+                self.nodes[root].parent = parent;
+                return Some(root);
+            }
+            (None, None) => return None,
+        };
+
+        let root = current;
+        self.nodes[root].parent = parent;
+
+        loop {
+            let go_right = self.rng.gen();
+            let node = &mut self.nodes[current];
+            let side = if go_right {
+                &mut node.right
+            } else {
+                &mut node.left
+            };
+
+            let Some(mut child) = *side else {
+                *side = Some(other);
+                self.nodes[other].parent = Some(current);
+                break;
+            };
+
+            if self.less(other, child) {
+                mem::swap(&mut child, &mut other);
+            }
+
+            let node = &mut self.nodes[current];
+            if go_right {
+                node.right = Some(child);
+            } else {
+                node.left = Some(child);
+            }
+
+            self.nodes[child].parent = Some(current);
+            current = child;
+        }
+
+        Some(root)
+    }
+
+    fn replace_child(&mut self, parent: Option<usize>, old: usize, new: Option<usize>) {
+        if let Some(parent) = parent {
+            let parent = &mut self.nodes[parent];
+            if parent.left == Some(old) {
+                parent.left = new;
+            } else {
+                parent.right = new;
+            }
+        } else {
+            self.root = new;
+        }
+    }
+}
+
+impl<K: Ord, D> Default for RandomizedMeldableHeap<K, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, D, R: Rng + SeedableRng, C: Compare<K, D> + Default> FromIterator<HeapEntry<K, D>>
+    for RandomizedMeldableHeap<K, D, R, C>
+{
+    fn from_iter<I: IntoIterator<Item = HeapEntry<K, D>>>(iter: I) -> Self {
+        RandomizedMeldableHeap::from_vec_with_rng_and_compare(
+            iter.into_iter().collect(),
+            R::from_entropy(),
+            C::default(),
+        )
+    }
+}
+
+impl<K, D, R: Rng, C: Compare<K, D>> MeldableHeap<K, D> for RandomizedMeldableHeap<K, D, R, C> {
+    fn meld(&mut self, mut other: Self) {
+        // Only the nodes of the heap with the smaller arena are moved.
+        let moves_self = self.nodes.slots() < other.nodes.slots();
+        let offset = if moves_self {
+            mem::swap(&mut self.nodes, &mut other.nodes);
+            mem::swap(&mut self.handles, &mut other.handles);
+            mem::swap(&mut self.root, &mut other.root);
+            self.sequence.append_to(&other.sequence)
+        } else {
+            self.sequence.append(&other.sequence)
+        };
+        let mapping = self.nodes.append(other.nodes);
+        self.handles.append(other.handles);
+        for &index in mapping.iter().flatten() {
+            let node = &mut self.nodes[index];
+            node.seq = node.seq.wrapping_add(offset);
+            node.parent = node.parent.map(|parent| mapping[parent].unwrap());
+            node.left = node.left.map(|left| mapping[left].unwrap());
+            node.right = node.right.map(|right| mapping[right].unwrap());
+
+            self.handles.set(node.handle, index);
+        }
+
+        let other_root = other.root.map(|root| mapping[root].unwrap());
+        self.root = self.meld_nodes(self.root, other_root, None);
+    }
+}
+
+/// Floyd's bottom-up construction of an implicit binary heap, whose layout `build` then turns
+/// into nodes.
+fn heapify<T>(items: &mut [T], less: impl Fn(&T, &T) -> bool) {
+    for start in (0..items.len() / 2).rev() {
+        let mut index = start;
+        loop {
+            let mut smallest = index;
+            for child in [2 * index + 1, 2 * index + 2] {
+                if child < items.len() && less(&items[child], &items[smallest]) {
+                    smallest = child;
+                }
+            }
+
+            if smallest == index {
+                break;
+            }
+
+            items.swap(index, smallest);
+            index = smallest;
+        }
+    }
+}
+
+impl<K, D, R, C> IntoIterator for RandomizedMeldableHeap<K, D, R, C> {
+    type Item = HeapEntry<K, D>;
+    type IntoIter = IntoIter<K, D>;
+
+    fn into_iter(self) -> IntoIter<K, D> {
+        IntoIter {
+            nodes: self.nodes.into_iter(),
+        }
+    }
+}
+
+pub struct IntoIter<K, D> {
+    nodes: <Arena<Node<K, D>> as IntoIterator>::IntoIter,
+}
+
+impl<K, D> Iterator for IntoIter<K, D> {
+    type Item = HeapEntry<K, D>;
+
+    fn next(&mut self) -> Option<HeapEntry<K, D>> {
+        self.nodes.next().map(|node| node.value)
+    }
+}
+
+pub struct RandomizedPeekMut<'a, K, D, R: Rng, C: Compare<K, D>> {
+    heap: &'a mut RandomizedMeldableHeap<K, D, R, C>,
+    modified: bool,
+}
+
+impl<K, D, R: Rng, C: Compare<K, D>> Deref for RandomizedPeekMut<'_, K, D, R, C> {
+    type Target = HeapEntry<K, D>;
+
+    fn deref(&self) -> &HeapEntry<K, D> {
+        &self.heap.nodes[self.heap.root.unwrap()].value
+    }
+}
+
+impl<K, D, R: Rng, C: Compare<K, D>> DerefMut for RandomizedPeekMut<'_, K, D, R, C> {
+    fn deref_mut(&mut self) -> &mut HeapEntry<K, D> {
+        self.modified = true;
+        let root = self.heap.root.unwrap();
+        &mut self.heap.nodes[root].value
+    }
+}
+
+impl<K, D, R: Rng, C: Compare<K, D>> Drop for RandomizedPeekMut<'_, K, D, R, C> {
+    fn drop(&mut self) {
+        if self.modified {
+            let root = self.heap.root.unwrap();
+            let left = self.heap.nodes[root].left.take();
+            let right = self.heap.nodes[root].right.take();
+
+            let rest = self.heap.meld_nodes(left, right, None);
+            self.heap.root = self.heap.meld_nodes(rest, Some(root), None);
+        }
+    }
+}
+
+impl<K, D, R: Rng, C: Compare<K, D>> Heap<K, D> for RandomizedMeldableHeap<K, D, R, C> {
+    type EntryRef = Handle;
+    type PeekMut<'a>
+        = RandomizedPeekMut<'a, K, D, R, C>
+    where
+        Self: 'a;
+
+    fn insert(&mut self, entry: HeapEntry<K, D>) -> Handle {
+        let seq = self.sequence.next();
+        let index = self.add_node(entry, seq);
+        self.root = self.meld_nodes(self.root, Some(index), None);
+
+        self.nodes[index].handle
+    }
+
+    fn delete_min(&mut self) -> Option<HeapEntry<K, D>> {
+        let root = self.root?;
+        self.remove(self.nodes[root].handle)
+    }
+
+    fn peek(&self) -> Option<&HeapEntry<K, D>> {
+        self.root.map(|root| &self.nodes[root].value)
+    }
+
+    fn remove(&mut self, reference: Handle) -> Option<HeapEntry<K, D>> {
+        let index = self.handles.remove(reference)?;
+        let Node {
+            value,
+            parent,
+            left,
+            right,
+            ..
+        } = self.nodes.remove(index);
+
+        let subtree = self.meld_nodes(left, right, parent);
+        self.replace_child(parent, index, subtree);
+
+        Some(value)
+    }
+
+    fn peek_mut(&mut self) -> Option<RandomizedPeekMut<'_, K, D, R, C>> {
+        if self.root.is_none() {
+            None
+        } else {
+            Some(RandomizedPeekMut {
+                heap: self,
+                modified: false,
+            })
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.handles.clear();
+        self.root = None;
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a HeapEntry<K, D>>
+    where
+        K: 'a,
+        D: 'a,
+    {
+        self.nodes.iter().map(|node| &node.value)
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = HeapEntry<K, D>> {
+        self.handles.clear();
+        self.root = None;
+
+        mem::replace(&mut self.nodes, Arena::new())
+            .into_iter()
+            .map(|node| node.value)
+    }
+}
+
+impl<K, D, R: Rng, C: Compare<K, D>> DecreaseKeyHeap<K, D> for RandomizedMeldableHeap<K, D, R, C> {
+    fn decrease_key(&mut self, reference: Handle, new_key: K) -> Result<(), HeapError> {
+        let index = self.handles.get(reference).ok_or(HeapError::StaleHandle)?;
+
+        let value = &self.nodes[index].value;
+        if self
+            .compare
+            .compare(&new_key, &value.data, &value.key, &value.data)
+            == Ordering::Greater
+        {
+            return Err(HeapError::KeyNotDecreased);
+        }
+
+        self.nodes[index].value.key = new_key;
+        if let Some(parent) = self.nodes[index].parent {
+            self.replace_child(Some(parent), index, None);
+            self.root = self.meld_nodes(self.root, Some(index), None);
+        }
+
+        Ok(())
+    }
+
+    fn change_key(&mut self, reference: Handle, new_key: K) -> Result<(), HeapError> {
+        let index = self.handles.get(reference).ok_or(HeapError::StaleHandle)?;
+
+        let value = &self.nodes[index].value;
+        if self
+            .compare
+            .compare(&new_key, &value.data, &value.key, &value.data)
+            != Ordering::Greater
+        {
+            return self.decrease_key(reference, new_key);
+        }
+
+        let Node {
+            parent,
+            left,
+            right,
+            ..
+        } = self.nodes[index];
+        let subtree = self.meld_nodes(left, right, parent);
+        self.replace_child(parent, index, subtree);
+
+        let node = &mut self.nodes[index];
+        node.value.key = new_key;
+        node.left = None;
+        node.right = None;
+        self.root = self.meld_nodes(self.root, Some(index), None);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use rand::rngs::mock::StepRng;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::RandomizedMeldableHeap;
+    use crate::{testing, ByData, Heap, HeapEntry, MaxOrder, MeldableHeap, MinOrder};
+
+    #[test]
+    fn matches_model() {
+        testing::check_decrease_key_against_model(RandomizedMeldableHeap::with_seed);
+    }
+
+    #[test]
+    fn default_heap_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<RandomizedMeldableHeap<u64, usize>>();
+    }
+
+    #[test]
+    fn stable_matches_model() {
+        testing::check_decrease_key_against_model(|seed| {
+            RandomizedMeldableHeap::with_rng_and_compare_stable(
+                StdRng::seed_from_u64(seed),
+                MinOrder,
+            )
+        });
+    }
+
+    #[test]
+    fn deep_heap_on_small_stack() {
+        thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(|| {
+                // A generator that always walks right turns descending inserts into a single
+                // spine as deep as the heap is large.
+                let always_right = || StepRng::new(u64::MAX, 0);
+                let deep = |keys: &mut dyn Iterator<Item = u64>| {
+                    let mut heap = RandomizedMeldableHeap::with_rng(always_right());
+                    for key in keys {
+                        heap.insert(HeapEntry { key, data: () });
+                    }
+                    heap
+                };
+
+                let mut heap = deep(&mut (0..200_000).rev().map(|key| 2 * key));
+                let mut depth = 0;
+                let mut node = heap.root;
+                while let Some(index) = node {
+                    depth += 1;
+                    node = heap.nodes[index].right;
+                }
+                assert_eq!(depth, 200_000);
+
+                heap.meld(deep(&mut (0..1000).rev().map(|key| 2 * key + 1)));
+                for expected in 0..2000 {
+                    assert_eq!(heap.delete_min().unwrap().key, expected);
+                }
+                assert_eq!(heap.len(), 199_000);
+
+                drop(heap);
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn max_order_matches_model() {
+        testing::check_compare_against_model(MaxOrder, |seed| {
+            RandomizedMeldableHeap::with_rng_and_compare(StdRng::seed_from_u64(seed), MaxOrder)
+        });
+    }
+
+    #[test]
+    fn by_data_matches_model() {
+        let by_data = ByData(|data: &usize| data % 10);
+        testing::check_compare_against_model(by_data, |seed| {
+            RandomizedMeldableHeap::with_rng_and_compare(StdRng::seed_from_u64(seed), by_data)
+        });
+    }
+
+    #[test]
+    fn closure_matches_model() {
+        let by_residue = |a: &u64, b: &u64| (a % 8).cmp(&(b % 8));
+        testing::check_compare_against_model(by_residue, |seed| {
+            RandomizedMeldableHeap::with_rng_and_compare(StdRng::seed_from_u64(seed), by_residue)
+        });
+    }
+
+    #[test]
+    fn drain_and_iterators_round_trip() {
+        testing::check_drain_and_iterators(RandomizedMeldableHeap::new);
+    }
+
+    #[test]
+    fn meld_keeps_handles_of_both_heaps() {
+        testing::check_meld_handles(RandomizedMeldableHeap::new);
+    }
+
+    #[test]
+    fn stable_keeps_fifo_order() {
+        testing::check_fifo(
+            RandomizedMeldableHeap::new_stable,
+            RandomizedMeldableHeap::from_vec_stable,
+        );
+    }
+
+    #[test]
+    fn stable_decrease_key_keeps_fifo_order() {
+        testing::check_fifo_decrease_key(RandomizedMeldableHeap::new_stable);
+    }
+}
@@ -0,0 +1,74 @@
+use std::cmp::Ordering;
+
+/// Per-heap insertion counter. Every entry is stamped with the next number when it is added, and a
+/// stable heap breaks ties between equal keys on it so they come out in FIFO order. Stability is
+/// fixed when the heap is built, by its `_stable` constructors.
+#[derive(Debug)]
+pub(crate) struct Sequence {
+    stable: bool,
+    start: u64,
+    next: u64,
+}
+
+/// Numbering starts halfway through the range, which leaves room below it for the entries of a
+/// heap that is melded in ahead of this one's.
+const ORIGIN: u64 = 1 << 63;
+
+impl Sequence {
+    pub(crate) fn new() -> Sequence {
+        Sequence {
+            stable: false,
+            start: ORIGIN,
+            next: ORIGIN,
+        }
+    }
+
+    pub(crate) fn stable() -> Sequence {
+        Sequence {
+            stable: true,
+            ..Sequence::new()
+        }
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.next += 1;
+        self.next - 1
+    }
+
+    /// Makes room for the entries of `other`, which keep their relative order after every entry
+    /// already stamped by `self`. Returns the offset to add, wrapping, to their numbers.
+    pub(crate) fn append(&mut self, other: &Sequence) -> u64 {
+        let offset = self.next.wrapping_sub(other.start);
+        self.next += other.next - other.start;
+
+        offset
+    }
+
+    /// Like `append`, but keeps the numbers of `other` and renumbers the entries of `self`
+    /// instead, for when they are the ones being moved. Returns the offset to add, wrapping, to
+    /// the numbers of `self`.
+    #[cfg(any(
+        feature = "binomial",
+        feature = "fibonacci",
+        feature = "leftist",
+        feature = "pairing",
+        feature = "randomized",
+        feature = "skew"
+    ))]
+    pub(crate) fn append_to(&mut self, other: &Sequence) -> u64 {
+        let start = other.start - (self.next - self.start);
+        let offset = start.wrapping_sub(self.start);
+        self.start = start;
+        self.next = other.next;
+
+        offset
+    }
+
+    pub(crate) fn tiebreak(&self, ordering: Ordering, a: u64, b: u64) -> Ordering {
+        if self.stable {
+            ordering.then(a.cmp(&b))
+        } else {
+            ordering
+        }
+    }
+}
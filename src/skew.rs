@@ -0,0 +1,352 @@
+use std::collections::VecDeque;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+use crate::arena::Arena;
+use crate::handle::{Handle, HandleMap};
+use crate::sequence::Sequence;
+use crate::{Heap, HeapEntry, MeldableHeap};
+
+struct SkewNode<K, D> {
+    entry: HeapEntry<K, D>,
+    handle: Handle,
+    seq: u64,
+    parent: Option<usize>,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+pub struct SkewHeap<K, D> {
+    nodes: Arena<SkewNode<K, D>>,
+    handles: HandleMap,
+    root: Option<usize>,
+    sequence: Sequence,
+}
+
+impl<K: Ord, D> SkewHeap<K, D> {
+    pub fn new() -> SkewHeap<K, D> {
+        SkewHeap::empty(Sequence::new())
+    }
+
+    pub fn new_stable() -> SkewHeap<K, D> {
+        SkewHeap::empty(Sequence::stable())
+    }
+
+    pub fn from_vec(entries: Vec<HeapEntry<K, D>>) -> SkewHeap<K, D> {
+        SkewHeap::build(entries, Sequence::new())
+    }
+
+    /// Like `from_vec`, with ties kept in the order of `entries`.
+    pub fn from_vec_stable(entries: Vec<HeapEntry<K, D>>) -> SkewHeap<K, D> {
+        SkewHeap::build(entries, Sequence::stable())
+    }
+
+    fn empty(sequence: Sequence) -> SkewHeap<K, D> {
+        SkewHeap {
+            nodes: Arena::new(),
+            handles: HandleMap::new(),
+            root: None,
+            sequence,
+        }
+    }
+
+    fn build(entries: Vec<HeapEntry<K, D>>, sequence: Sequence) -> SkewHeap<K, D> {
+        let mut heap = SkewHeap::empty(sequence);
+        let mut queue = entries
+            .into_iter()
+            .map(|entry| heap.add_node(entry))
+            .collect::<VecDeque<_>>();
+
+        while queue.len() > 1 {
+            let first = queue.pop_front();
+            let second = queue.pop_front();
+            queue.extend(heap.meld_nodes(first, second, None));
+        }
+
+        heap.root = queue.pop_front();
+
+        heap
+    }
+
+    fn less(&self, a: usize, b: usize) -> bool {
+        let (a, b) = (&self.nodes[a], &self.nodes[b]);
+        let ordering = a.entry.key.cmp(&b.entry.key);
+        self.sequence.tiebreak(ordering, a.seq, b.seq).is_lt()
+    }
+
+    fn add_node(&mut self, entry: HeapEntry<K, D>) -> usize {
+        let handle = self.handles.insert(0);
+        let index = self.nodes.insert(SkewNode {
+            entry,
+            handle,
+            seq: self.sequence.next(),
+            parent: None,
+            left: None,
+            right: None,
+        });
+        self.handles.set(handle, index);
+
+        index
+    }
+
+    fn meld_nodes(
+        &mut self,
+        this: Option<usize>,
+        other: Option<usize>,
+        parent: Option<usize>,
+    ) -> Option<usize> {
+        let (mut current, mut other) = match (this, other) {
+            (Some(this), Some(other)) if self.less(other, this) => (other, this),
+            (Some(this), Some(other)) => (this, other),
+            (Some(root), None) | (None, Some(root)) => {
+                self.nodes[root].parent = parent;
+                return Some(root);
+            }
+            (None, None) => return None,
+        };
+
+        let root = current;
+        self.nodes[root].parent = parent;
+
+        loop {
+            let right = self.nodes[current].right;
+            self.nodes[current].right = self.nodes[current].left;
+
+            let Some(mut right) = right else {
+                self.nodes[current].left = Some(other);
+                self.nodes[other].parent = Some(current);
+                break;
+            };
+
+            if self.less(other, right) {
+                mem::swap(&mut right, &mut other);
+            }
+
+            self.nodes[current].left = Some(right);
+            self.nodes[right].parent = Some(current);
+            current = right;
+        }
+
+        Some(root)
+    }
+
+    fn replace_child(&mut self, parent: Option<usize>, old: usize, new: Option<usize>) {
+        if let Some(parent) = parent {
+            let parent = &mut self.nodes[parent];
+            if parent.left == Some(old) {
+                parent.left = new;
+            } else {
+                parent.right = new;
+            }
+        } else {
+            self.root = new;
+        }
+    }
+}
+
+impl<K: Ord, D> Default for SkewHeap<K, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, D> FromIterator<HeapEntry<K, D>> for SkewHeap<K, D> {
+    fn from_iter<I: IntoIterator<Item = HeapEntry<K, D>>>(iter: I) -> Self {
+        SkewHeap::from_vec(iter.into_iter().collect())
+    }
+}
+
+impl<K, D> IntoIterator for SkewHeap<K, D> {
+    type Item = HeapEntry<K, D>;
+    type IntoIter = IntoIter<K, D>;
+
+    fn into_iter(self) -> IntoIter<K, D> {
+        IntoIter {
+            nodes: self.nodes.into_iter(),
+        }
+    }
+}
+
+pub struct IntoIter<K, D> {
+    nodes: <Arena<SkewNode<K, D>> as IntoIterator>::IntoIter,
+}
+
+impl<K, D> Iterator for IntoIter<K, D> {
+    type Item = HeapEntry<K, D>;
+
+    fn next(&mut self) -> Option<HeapEntry<K, D>> {
+        self.nodes.next().map(|node| node.entry)
+    }
+}
+
+pub struct SkewPeekMut<'a, K: Ord, D> {
+    heap: &'a mut SkewHeap<K, D>,
+    modified: bool,
+}
+
+impl<K: Ord, D> Deref for SkewPeekMut<'_, K, D> {
+    type Target = HeapEntry<K, D>;
+
+    fn deref(&self) -> &HeapEntry<K, D> {
+        &self.heap.nodes[self.heap.root.unwrap()].entry
+    }
+}
+
+impl<K: Ord, D> DerefMut for SkewPeekMut<'_, K, D> {
+    fn deref_mut(&mut self) -> &mut HeapEntry<K, D> {
+        self.modified = true;
+        let root = self.heap.root.unwrap();
+        &mut self.heap.nodes[root].entry
+    }
+}
+
+impl<K: Ord, D> Drop for SkewPeekMut<'_, K, D> {
+    fn drop(&mut self) {
+        if self.modified {
+            let root = self.heap.root.unwrap();
+            let left = self.heap.nodes[root].left.take();
+            let right = self.heap.nodes[root].right.take();
+
+            let rest = self.heap.meld_nodes(left, right, None);
+            self.heap.root = self.heap.meld_nodes(rest, Some(root), None);
+        }
+    }
+}
+
+impl<K: Ord, D> Heap<K, D> for SkewHeap<K, D> {
+    type EntryRef = Handle;
+    type PeekMut<'a>
+        = SkewPeekMut<'a, K, D>
+    where
+        Self: 'a;
+
+    fn insert(&mut self, entry: HeapEntry<K, D>) -> Handle {
+        let index = self.add_node(entry);
+        self.root = self.meld_nodes(self.root, Some(index), None);
+
+        self.nodes[index].handle
+    }
+
+    fn delete_min(&mut self) -> Option<HeapEntry<K, D>> {
+        let root = self.root?;
+        self.remove(self.nodes[root].handle)
+    }
+
+    fn peek(&self) -> Option<&HeapEntry<K, D>> {
+        self.root.map(|root| &self.nodes[root].entry)
+    }
+
+    fn remove(&mut self, reference: Handle) -> Option<HeapEntry<K, D>> {
+        let index = self.handles.remove(reference)?;
+        let SkewNode {
+            entry,
+            parent,
+            left,
+            right,
+            ..
+        } = self.nodes.remove(index);
+
+        let subtree = self.meld_nodes(left, right, parent);
+        self.replace_child(parent, index, subtree);
+
+        Some(entry)
+    }
+
+    fn peek_mut(&mut self) -> Option<SkewPeekMut<'_, K, D>> {
+        if self.root.is_none() {
+            None
+        } else {
+            Some(SkewPeekMut {
+                heap: self,
+                modified: false,
+            })
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.handles.clear();
+        self.root = None;
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = &'a HeapEntry<K, D>>
+    where
+        K: 'a,
+        D: 'a,
+    {
+        self.nodes.iter().map(|node| &node.entry)
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = HeapEntry<K, D>> {
+        self.handles.clear();
+        self.root = None;
+
+        mem::replace(&mut self.nodes, Arena::new())
+            .into_iter()
+            .map(|node| node.entry)
+    }
+}
+
+impl<K: Ord, D> MeldableHeap<K, D> for SkewHeap<K, D> {
+    fn meld(&mut self, mut other: Self) {
+        // Only the nodes of the heap with the smaller arena are moved.
+        let moves_self = self.nodes.slots() < other.nodes.slots();
+        let offset = if moves_self {
+            mem::swap(&mut self.nodes, &mut other.nodes);
+            mem::swap(&mut self.handles, &mut other.handles);
+            mem::swap(&mut self.root, &mut other.root);
+            self.sequence.append_to(&other.sequence)
+        } else {
+            self.sequence.append(&other.sequence)
+        };
+        let mapping = self.nodes.append(other.nodes);
+        self.handles.append(other.handles);
+        for &index in mapping.iter().flatten() {
+            let node = &mut self.nodes[index];
+            node.seq = node.seq.wrapping_add(offset);
+            node.parent = node.parent.map(|parent| mapping[parent].unwrap());
+            node.left = node.left.map(|left| mapping[left].unwrap());
+            node.right = node.right.map(|right| mapping[right].unwrap());
+
+            self.handles.set(node.handle, index);
+        }
+
+        let other_root = other.root.map(|root| mapping[root].unwrap());
+        self.root = self.meld_nodes(self.root, other_root, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SkewHeap;
+    use crate::testing;
+
+    #[test]
+    fn matches_model() {
+        testing::check_against_model(|_| SkewHeap::new());
+    }
+
+    #[test]
+    fn stable_matches_model() {
+        testing::check_against_model(|_| SkewHeap::new_stable());
+    }
+
+    #[test]
+    fn drain_and_iterators_round_trip() {
+        testing::check_drain_and_iterators(SkewHeap::new);
+    }
+
+    #[test]
+    fn meld_keeps_handles_of_both_heaps() {
+        testing::check_meld_handles(SkewHeap::new);
+    }
+
+    #[test]
+    fn stable_keeps_fifo_order() {
+        testing::check_fifo(SkewHeap::new_stable, SkewHeap::from_vec_stable);
+    }
+}
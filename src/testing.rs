@@ -0,0 +1,409 @@
+//! Model-based checks shared by the unit tests of every heap.
+
+use std::cmp::Ordering::{Equal, Greater};
+use std::collections::BTreeSet;
+
+use crate::{Compare, Heap, HeapEntry, HeapError, MeldableHeap};
+
+#[cfg(any(
+    feature = "binomial",
+    feature = "dary",
+    feature = "fibonacci",
+    feature = "pairing",
+    feature = "randomized"
+))]
+pub(crate) use decrease_key::{check_decrease_key_against_model, check_fifo_decrease_key};
+
+#[cfg(any(feature = "binomial", feature = "dary", feature = "randomized"))]
+pub(crate) use decrease_key::check_compare_against_model;
+
+/// Entries with a handful of repeated keys, carrying their position as data.
+fn duplicates(range: std::ops::Range<usize>) -> Vec<HeapEntry<u64, usize>> {
+    range
+        .map(|data| HeapEntry {
+            key: (data * 7 % 5) as u64,
+            data,
+        })
+        .collect()
+}
+
+/// Checks that the data of every entry comes out in insertion order among equal keys.
+fn assert_fifo<H: Heap<u64, usize>>(heap: H) {
+    let order = heap
+        .into_sorted_iter()
+        .map(|entry| (entry.key, entry.data))
+        .collect::<Vec<_>>();
+
+    let mut expected = order.clone();
+    expected.sort();
+    assert_eq!(order, expected);
+}
+
+/// Checks FIFO order among equal keys for stable heaps filled by `insert`, by `from_vec_stable`,
+/// by melding two of them of either size and by interleaving inserts with deletions.
+pub(crate) fn check_fifo<H: MeldableHeap<u64, usize>>(
+    new_stable: impl Fn() -> H,
+    from_vec_stable: impl Fn(Vec<HeapEntry<u64, usize>>) -> H,
+) {
+    let mut heap = new_stable();
+    for entry in duplicates(0..200) {
+        heap.insert(entry);
+    }
+    assert_fifo(heap);
+
+    assert_fifo(from_vec_stable(duplicates(0..200)));
+
+    // Melds move the smaller heap, so try it on either side.
+    for split in [10, 100, 190] {
+        let mut heap = new_stable();
+        for entry in duplicates(0..split) {
+            heap.insert(entry);
+        }
+        heap.meld(from_vec_stable(duplicates(split..200)));
+        assert_fifo(heap);
+    }
+
+    let mut heap = from_vec_stable(duplicates(0..100));
+    let mut deleted = Vec::new();
+    for entry in duplicates(100..200) {
+        heap.insert(entry);
+        if heap.len() % 2 == 0 {
+            let entry = heap.delete_min().unwrap();
+            deleted.push((entry.key, entry.data));
+        }
+    }
+    for (key, data) in deleted {
+        // Anything inserted before the deleted entry with the same key must already be gone.
+        assert!(heap
+            .iter()
+            .all(|entry| entry.key != key || entry.data > data));
+    }
+    assert_fifo(heap);
+}
+
+/// Checks that the handles of both heaps keep reaching their own entries after a meld, whichever
+/// side is larger, and that handles from an unrelated heap reach nothing.
+pub(crate) fn check_meld_handles<H>(new: impl Fn() -> H)
+where
+    H: MeldableHeap<u64, usize>,
+    H::EntryRef: Clone,
+{
+    for (left, right) in [(0..10, 10..300), (0..290, 290..300), (0..150, 150..300)] {
+        let mut heap = new();
+        let mut other = new();
+        let mut references = Vec::new();
+        for entry in duplicates(left) {
+            references.push((entry.data, heap.insert(entry)));
+        }
+        for entry in duplicates(right) {
+            references.push((entry.data, other.insert(entry)));
+        }
+
+        let mut unrelated = new();
+        let unrelated_reference = unrelated.insert(HeapEntry { key: 0, data: 300 });
+        for (_, reference) in &references {
+            assert!(unrelated.remove(reference.clone()).is_none());
+        }
+        assert_eq!(unrelated.len(), 1);
+
+        heap.meld(other);
+        assert!(heap.remove(unrelated_reference).is_none());
+        assert_eq!(heap.len(), 300);
+
+        for (data, reference) in references.iter().filter(|(data, _)| data % 2 == 1) {
+            assert_eq!(heap.remove(reference.clone()).unwrap().data, *data);
+            assert!(heap.remove(reference.clone()).is_none());
+        }
+
+        let remaining = heap
+            .drain()
+            .map(|entry| entry.data)
+            .collect::<BTreeSet<_>>();
+        assert!(remaining.iter().copied().eq((0..300).step_by(2)));
+    }
+}
+
+/// Checks that `drain` yields every entry and leaves the heap empty, with every handle stale but
+/// the heap still usable, and that `into_iter` and `collect` round-trip the same entries.
+pub(crate) fn check_drain_and_iterators<H>(new: impl Fn() -> H)
+where
+    H: Heap<u64, usize> + IntoIterator<Item = HeapEntry<u64, usize>>,
+    H: FromIterator<HeapEntry<u64, usize>>,
+    H::EntryRef: Clone,
+{
+    let entries = |heap: H| {
+        let mut entries = heap
+            .into_iter()
+            .map(|entry| (entry.data, entry.key))
+            .collect::<Vec<_>>();
+        entries.sort();
+        entries
+    };
+    let expected = duplicates(0..200)
+        .into_iter()
+        .map(|entry| (entry.data, entry.key))
+        .collect::<Vec<_>>();
+
+    let mut heap = new();
+    let references = duplicates(0..200)
+        .into_iter()
+        .map(|entry| heap.insert(entry))
+        .collect::<Vec<_>>();
+    let mut deleted = (0..20)
+        .map(|_| heap.delete_min().unwrap().data)
+        .collect::<BTreeSet<_>>();
+
+    let drained = heap
+        .drain()
+        .map(|entry| entry.data)
+        .collect::<BTreeSet<_>>();
+    assert_eq!(drained.len(), 180);
+    deleted.extend(&drained);
+    assert!(deleted.into_iter().eq(0..200));
+
+    assert!(heap.is_empty());
+    assert!(heap.peek().is_none());
+    assert!(heap.delete_min().is_none());
+    heap.insert(HeapEntry { key: 3, data: 200 });
+    for reference in references {
+        assert!(heap.remove(reference).is_none());
+    }
+    assert_eq!(heap.delete_min().map(|entry| entry.data), Some(200));
+
+    let mut heap = new();
+    for entry in duplicates(0..200) {
+        heap.insert(entry);
+    }
+    assert_eq!(entries(heap), expected);
+
+    let heap = duplicates(0..200).into_iter().collect::<H>();
+    assert_eq!(heap.len(), 200);
+    assert_eq!(entries(heap), expected);
+
+    let heap = duplicates(0..200).into_iter().collect::<H>();
+    let keys = heap
+        .into_sorted_iter()
+        .map(|entry| entry.key)
+        .collect::<Vec<_>>();
+    assert!(keys.windows(2).all(|pair| pair[0] <= pair[1]));
+}
+
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound
+    }
+}
+
+const SEEDS: u64 = 300;
+const OPERATIONS: usize = 400;
+const KEYS: u64 = 64;
+
+type ChangeKey<H> =
+    fn(&mut H, <H as Heap<u64, usize>>::EntryRef, u64, bool) -> Result<(), HeapError>;
+
+/// Runs random inserts, deletions, removals, `peek_mut` updates and clears against a plain list of
+/// live entries. Entries carry their insertion index as data.
+#[cfg(any(feature = "leftist", feature = "skew"))]
+pub(crate) fn check_against_model<H>(new: impl Fn(u64) -> H)
+where
+    H: Heap<u64, usize>,
+    H::EntryRef: Clone,
+{
+    for seed in 0..SEEDS {
+        run_model(new(seed), seed, &crate::MinOrder, None);
+    }
+}
+
+fn run_model<H, C>(mut heap: H, seed: u64, compare: &C, change_key: Option<ChangeKey<H>>)
+where
+    H: Heap<u64, usize>,
+    H::EntryRef: Clone,
+    C: Compare<u64, usize>,
+{
+    let mut rng = Rng::new(seed);
+    let mut references = Vec::new();
+    let mut keys: Vec<Option<u64>> = Vec::new();
+
+    // The first live entry in heap order. Entries the comparator ties with it may come out instead.
+    let top = |keys: &[Option<u64>]| {
+        (0..keys.len())
+            .filter_map(|data| keys[data].map(|key| HeapEntry { key, data }))
+            .min_by(|a, b| compare.compare_entries(a, b))
+    };
+    let assert_top =
+        |entry: Option<&HeapEntry<u64, usize>>, keys: &[Option<u64>]| match (entry, top(keys)) {
+            (Some(entry), Some(top)) => assert_eq!(compare.compare_entries(entry, &top), Equal),
+            (entry, top) => assert_eq!(entry.is_none(), top.is_none()),
+        };
+
+    for _ in 0..OPERATIONS {
+        let target = (!references.is_empty()).then(|| rng.below(references.len() as u64) as usize);
+
+        match rng.below(20) {
+            0..=6 => {
+                let key = rng.below(KEYS);
+                references.push(heap.insert(HeapEntry {
+                    key,
+                    data: keys.len(),
+                }));
+                keys.push(Some(key));
+            }
+            7..=9 => {
+                let entry = heap.delete_min();
+                assert_top(entry.as_ref(), &keys);
+                if let Some(HeapEntry { key, data }) = entry {
+                    assert_eq!(keys[data].take(), Some(key));
+                }
+            }
+            10..=11 => {
+                let Some(target) = target else { continue };
+                match heap.remove(references[target].clone()) {
+                    Some(HeapEntry { key, data }) => {
+                        assert_eq!(data, target);
+                        assert_eq!(keys[target].take(), Some(key));
+                    }
+                    None => assert_eq!(keys[target], None),
+                }
+            }
+            12..=16 => {
+                let (Some(target), Some(change_key)) = (target, change_key) else {
+                    continue;
+                };
+                let key = rng.below(KEYS);
+                let decrease = rng.below(2) == 0;
+                let result = change_key(&mut heap, references[target].clone(), key, decrease);
+
+                match keys[target] {
+                    None => assert_eq!(result, Err(HeapError::StaleHandle)),
+                    Some(current)
+                        if decrease
+                            && compare.compare(&key, &target, &current, &target) == Greater =>
+                    {
+                        assert_eq!(result, Err(HeapError::KeyNotDecreased))
+                    }
+                    Some(_) => {
+                        assert_eq!(result, Ok(()));
+                        keys[target] = Some(key);
+                    }
+                }
+            }
+            17..=18 => {
+                let key = rng.below(KEYS);
+                match heap.peek_mut() {
+                    Some(mut entry) => {
+                        assert_top(Some(&entry), &keys);
+                        let data = entry.data;
+                        if rng.below(2) == 0 {
+                            entry.key = key;
+                            keys[data] = Some(key);
+                        }
+                    }
+                    None => assert!(top(&keys).is_none()),
+                }
+            }
+            _ => {
+                if rng.below(10) == 0 {
+                    heap.clear();
+                    keys.iter_mut().for_each(|key| *key = None);
+                }
+            }
+        }
+
+        assert_top(heap.peek(), &keys);
+        assert_eq!(heap.len(), keys.iter().flatten().count());
+    }
+
+    let sorted = heap.into_sorted_iter().collect::<Vec<_>>();
+    assert!(sorted
+        .windows(2)
+        .all(|pair| compare.compare_entries(&pair[0], &pair[1]) != Greater));
+
+    let mut entries = sorted
+        .into_iter()
+        .map(|entry| (entry.data, entry.key))
+        .collect::<Vec<_>>();
+    entries.sort();
+    let expected = keys
+        .into_iter()
+        .enumerate()
+        .filter_map(|(data, key)| Some((data, key?)))
+        .collect::<Vec<_>>();
+    assert_eq!(entries, expected);
+}
+
+#[cfg(any(
+    feature = "binomial",
+    feature = "dary",
+    feature = "fibonacci",
+    feature = "pairing",
+    feature = "randomized"
+))]
+mod decrease_key {
+    //! Checks for the heaps that hand out handles for `decrease_key` and `change_key`.
+
+    use super::{assert_fifo, run_model, SEEDS};
+    use crate::{Compare, DecreaseKeyHeap, HeapEntry, MinOrder};
+
+    /// Like `check_against_model`, with `decrease_key` and `change_key` calls mixed in.
+    pub(crate) fn check_decrease_key_against_model<H>(new: impl Fn(u64) -> H)
+    where
+        H: DecreaseKeyHeap<u64, usize>,
+        H::EntryRef: Clone,
+    {
+        check_compare_against_model(MinOrder, new);
+    }
+
+    /// Like `check_decrease_key_against_model`, for heaps ordered by `compare`. Under it,
+    /// decreasing a key means moving it towards the top, whatever that does to the key itself.
+    pub(crate) fn check_compare_against_model<H, C>(compare: C, new: impl Fn(u64) -> H)
+    where
+        H: DecreaseKeyHeap<u64, usize>,
+        H::EntryRef: Clone,
+        C: Compare<u64, usize>,
+    {
+        for seed in 0..SEEDS {
+            run_model(
+                new(seed),
+                seed,
+                &compare,
+                Some(|heap, reference, key, decrease| {
+                    if decrease {
+                        heap.decrease_key(reference, key)
+                    } else {
+                        heap.change_key(reference, key)
+                    }
+                }),
+            );
+        }
+    }
+
+    /// Checks that an entry decreased to a key that others already have keeps its own place in
+    /// insertion order among them, including a decrease to its current key.
+    pub(crate) fn check_fifo_decrease_key<H>(new_stable: impl Fn() -> H)
+    where
+        H: DecreaseKeyHeap<u64, usize>,
+        H::EntryRef: Clone,
+    {
+        let mut heap = new_stable();
+        let references = (0..200)
+            .map(|data| {
+                let key = if data % 3 == 0 { 10 } else { 5 };
+                heap.insert(HeapEntry { key, data })
+            })
+            .collect::<Vec<_>>();
+
+        for data in (0..200).rev() {
+            heap.decrease_key(references[data].clone(), 5).unwrap();
+        }
+        assert_fifo(heap);
+    }
+}
@@ -0,0 +1,327 @@
+//! Seedable operation sequences for exercising heaps with more than shuffled permutations.
+//! Generators only describe the operations; `run` and `run_decrease_key` replay them on a heap.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::{DecreaseKeyHeap, Heap, HeapEntry};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Insert(u64),
+    DeleteMin,
+    /// Lowers the key of the `entry`-th inserted entry, counting from zero, to `key`.
+    DecreaseKey {
+        entry: usize,
+        key: u64,
+    },
+}
+
+/// Relative weights of the operations in a mixed workload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mix {
+    pub insert: u32,
+    pub delete_min: u32,
+    pub decrease_key: u32,
+}
+
+/// Inserts `0..n` in increasing order, then deletes everything.
+pub fn sorted(n: usize) -> Vec<Operation> {
+    fill_and_drain((0..n as u64).collect())
+}
+
+/// Inserts `0..n` in decreasing order, then deletes everything.
+pub fn reverse_sorted(n: usize) -> Vec<Operation> {
+    fill_and_drain((0..n as u64).rev().collect())
+}
+
+/// Inserts `n` keys drawn uniformly from `0..distinct`, then deletes everything.
+///
+/// Panics if `distinct` is zero.
+pub fn duplicates(n: usize, distinct: u64, seed: u64) -> Vec<Operation> {
+    assert!(distinct > 0, "duplicates needs at least one distinct key");
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    fill_and_drain((0..n).map(|_| rng.gen_range(0..distinct)).collect())
+}
+
+/// Inserts `n` keys from `0..distinct` where key `k` is drawn with probability proportional to
+/// `1 / (k + 1)^exponent`, then deletes everything.
+///
+/// Panics if `distinct` is zero.
+pub fn zipf(n: usize, distinct: usize, exponent: f64, seed: u64) -> Vec<Operation> {
+    assert!(distinct > 0, "zipf needs at least one distinct key");
+
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut cumulative = Vec::with_capacity(distinct);
+    let mut total = 0.0;
+    for rank in 1..=distinct {
+        total += (rank as f64).powf(-exponent);
+        cumulative.push(total);
+    }
+
+    let keys = (0..n)
+        .map(|_| {
+            let target = rng.gen::<f64>() * total;
+            cumulative
+                .partition_point(|&weight| weight <= target)
+                .min(distinct - 1) as u64
+        })
+        .collect();
+
+    fill_and_drain(keys)
+}
+
+/// Fills the heap with `height` random keys and empties it again, `teeth` times, so its size
+/// traces a sawtooth.
+pub fn sawtooth(teeth: usize, height: usize, seed: u64) -> Vec<Operation> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut operations = Vec::with_capacity(2 * teeth * height);
+    for _ in 0..teeth {
+        operations.extend((0..height).map(|_| Operation::Insert(rng.gen())));
+        operations.extend((0..height).map(|_| Operation::DeleteMin));
+    }
+
+    operations
+}
+
+/// The hold model: `size` random inserts, then `holds` rounds of deleting the minimum and
+/// inserting it again plus an increment drawn from `0..=max_increment`.
+pub fn hold(size: usize, holds: usize, max_increment: u64, seed: u64) -> Vec<Operation> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut heap = BinaryHeap::with_capacity(size);
+
+    let mut operations = Vec::with_capacity(size + 2 * holds);
+    for _ in 0..size {
+        let key = rng.gen_range(0..=max_increment);
+        heap.push(Reverse(key));
+        operations.push(Operation::Insert(key));
+    }
+
+    for _ in 0..holds {
+        let Some(Reverse(min)) = heap.pop() else {
+            break;
+        };
+        let key = min.saturating_add(rng.gen_range(0..=max_increment));
+        heap.push(Reverse(key));
+        operations.extend([Operation::DeleteMin, Operation::Insert(key)]);
+    }
+
+    operations
+}
+
+/// `operations` random operations chosen by the weights of `mix`. Deletions and decreases on an
+/// empty heap become inserts. Keys are distinct at all times, so every heap removes the same
+/// entries and decreases always refer to entries still in the heap.
+pub fn mixed(operations: usize, mix: Mix, seed: u64) -> Vec<Operation> {
+    let total = mix.insert + mix.delete_min + mix.decrease_key;
+    assert!(total > 0, "mix has no operations");
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut by_key = BTreeSet::new();
+    let mut keys = Vec::new();
+    let mut live = Vec::new();
+    let mut positions = Vec::new();
+
+    let mut generated = Vec::with_capacity(operations);
+    while generated.len() < operations {
+        let roll = rng.gen_range(0..total);
+        let operation = if live.is_empty() || roll < mix.insert {
+            None
+        } else if roll < mix.insert + mix.delete_min {
+            let (_, entry) = by_key.pop_first().unwrap();
+            let position = positions[entry];
+            live.swap_remove(position);
+            if let Some(&moved) = live.get(position) {
+                positions[moved] = position;
+            }
+            Some(Operation::DeleteMin)
+        } else {
+            let entry = live[rng.gen_range(0..live.len())];
+            let current = keys[entry];
+            // Gives up after a few collisions and inserts instead.
+            let key = match current {
+                0 => None,
+                _ => (0..4)
+                    .map(|_| rng.gen_range(0..current))
+                    .find(|&key| !contains_key(&by_key, key)),
+            };
+            key.map(|key| {
+                by_key.remove(&(current, entry));
+                by_key.insert((key, entry));
+                keys[entry] = key;
+                Operation::DecreaseKey { entry, key }
+            })
+        };
+
+        let operation = operation.unwrap_or_else(|| {
+            let key = loop {
+                let key = rng.gen_range(0..u32::MAX as u64);
+                if !contains_key(&by_key, key) {
+                    break key;
+                }
+            };
+            let entry = keys.len();
+            by_key.insert((key, entry));
+            keys.push(key);
+            positions.push(live.len());
+            live.push(entry);
+            Operation::Insert(key)
+        });
+
+        generated.push(operation);
+    }
+
+    generated
+}
+
+/// Replays `operations` on `heap`, which should start empty, and returns the keys removed by
+/// each `DeleteMin`. Decreases are done by removing and reinserting the entry, so any heap
+/// works. Each entry is inserted with its position among the inserts as data.
+///
+/// Panics if an operation refers to an entry that is not in the heap.
+pub fn run<H>(heap: &mut H, operations: &[Operation]) -> Vec<u64>
+where
+    H: Heap<u64, usize>,
+    H::EntryRef: Clone,
+{
+    replay(heap, operations, |heap, reference, entry, key| {
+        heap.remove(reference).expect("entry is not in the heap");
+        heap.insert(HeapEntry { key, data: entry })
+    })
+}
+
+/// Like `run`, but performs decreases with `decrease_key`.
+pub fn run_decrease_key<H>(heap: &mut H, operations: &[Operation]) -> Vec<u64>
+where
+    H: DecreaseKeyHeap<u64, usize>,
+    H::EntryRef: Clone,
+{
+    replay(heap, operations, |heap, reference, _, key| {
+        heap.decrease_key(reference.clone(), key)
+            .expect("entry is not in the heap or the key did not decrease");
+        reference
+    })
+}
+
+fn replay<H>(
+    heap: &mut H,
+    operations: &[Operation],
+    mut decrease: impl FnMut(&mut H, H::EntryRef, usize, u64) -> H::EntryRef,
+) -> Vec<u64>
+where
+    H: Heap<u64, usize>,
+    H::EntryRef: Clone,
+{
+    let mut references = Vec::new();
+    let mut deleted = Vec::new();
+
+    for &operation in operations {
+        match operation {
+            Operation::Insert(key) => {
+                let entry = references.len();
+                references.push(Some(heap.insert(HeapEntry { key, data: entry })));
+            }
+            Operation::DeleteMin => {
+                if let Some(HeapEntry { key, data }) = heap.delete_min() {
+                    references[data] = None;
+                    deleted.push(key);
+                }
+            }
+            Operation::DecreaseKey { entry, key } => {
+                let reference = references[entry].take().expect("entry is not in the heap");
+                references[entry] = Some(decrease(heap, reference, entry, key));
+            }
+        }
+    }
+
+    deleted
+}
+
+fn fill_and_drain(keys: Vec<u64>) -> Vec<Operation> {
+    let n = keys.len();
+    keys.into_iter()
+        .map(Operation::Insert)
+        .chain((0..n).map(|_| Operation::DeleteMin))
+        .collect()
+}
+
+fn contains_key(by_key: &BTreeSet<(u64, usize)>, key: u64) -> bool {
+    by_key.range((key, 0)..=(key, usize::MAX)).next().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mix() -> Mix {
+        Mix {
+            insert: 4,
+            delete_min: 3,
+            decrease_key: 3,
+        }
+    }
+
+    #[test]
+    fn generators_are_deterministic_per_seed() {
+        assert_eq!(duplicates(500, 7, 1), duplicates(500, 7, 1));
+        assert_eq!(zipf(500, 50, 1.2, 1), zipf(500, 50, 1.2, 1));
+        assert_eq!(sawtooth(4, 50, 1), sawtooth(4, 50, 1));
+        assert_eq!(hold(100, 500, 50, 1), hold(100, 500, 50, 1));
+        assert_eq!(mixed(2000, mix(), 1), mixed(2000, mix(), 1));
+
+        assert_ne!(duplicates(500, 1000, 1), duplicates(500, 1000, 2));
+        assert_ne!(mixed(2000, mix(), 1), mixed(2000, mix(), 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one distinct key")]
+    fn duplicates_rejects_zero_distinct_keys() {
+        duplicates(10, 0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one distinct key")]
+    fn zipf_rejects_zero_distinct_keys() {
+        zipf(10, 0, 1.0, 1);
+    }
+
+    #[cfg(feature = "dary")]
+    #[test]
+    fn run_and_run_decrease_key_agree() {
+        use crate::dary::BinaryHeap;
+
+        for seed in 0..20 {
+            let operations = mixed(3000, mix(), seed);
+            assert!(operations
+                .iter()
+                .any(|operation| matches!(operation, Operation::DecreaseKey { .. })));
+
+            let removed = run(&mut BinaryHeap::new(), &operations);
+            assert_eq!(
+                run_decrease_key(&mut BinaryHeap::new(), &operations),
+                removed
+            );
+
+            let mut sorted = removed.clone();
+            sorted.sort();
+            assert_ne!(
+                removed, sorted,
+                "interleaved workloads should not drain in order"
+            );
+        }
+
+        for operations in [sorted(500), reverse_sorted(500), hold(100, 500, 50, 3)] {
+            let removed = run(&mut BinaryHeap::new(), &operations);
+            assert_eq!(
+                run_decrease_key(&mut BinaryHeap::new(), &operations),
+                removed
+            );
+        }
+    }
+}